@@ -2,24 +2,41 @@
 use std::fs;
 use std::io::{BufWriter, Write};
 use std::iter::FromIterator;
+use std::sync::Arc;
 //use std::sync::{Arc, Mutex};
 
 /* external crate*/
 use itertools::Itertools;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
 /* private use */
-use crate::cli::Params;
+use crate::cli::{AbacusArgs, Params};
 use crate::graph::*;
 use crate::io;
 use crate::util::*;
 
+#[derive(Debug, Clone)]
 pub struct AbacusAuxilliary {
     pub count: CountType,
     pub groups: HashMap<PathSegment, String>,
     pub include_coords: Option<Vec<PathSegment>>,
     pub exclude_coords: Option<Vec<PathSegment>>,
     pub order: Option<Vec<PathSegment>>,
+    // weight of each group as given in the order list; groups without weight count as 1
+    pub order_weights: Option<HashMap<String, f64>>,
+    // only nodes with depth (as given by S-line tag) within this range are counted
+    pub depth_range: Option<(f64, f64)>,
+    // the reference path is used as coordinate system only and is itself never counted
+    pub reference: Option<PathSegment>,
+    pub reference_coords: Option<Vec<PathSegment>>,
+    // if set, items visited by the reference path are excluded rather than those not visited
+    pub relative_to_reference: bool,
+    // if set, items are counted once per traversal rather than once per group
+    pub multiplicity: bool,
+    // if set, overrides the number of shards of the item table, which is otherwise adapted to the
+    // number of items
+    pub shards: Option<usize>,
 }
 
 impl AbacusAuxilliary {
@@ -27,136 +44,250 @@ impl AbacusAuxilliary {
         params: &Params,
         graph_aux: &GraphAuxilliary,
     ) -> Result<Self, std::io::Error> {
-        match params {
-            Params::Histgrowth {
-                count,
-                positive_list,
-                negative_list,
-                groupby,
-                groupby_sample,
-                groupby_haplotype,
-                ..
-            }
-            | Params::Hist {
-                count,
-                positive_list,
-                negative_list,
-                groupby,
-                groupby_sample,
-                groupby_haplotype,
-                ..
-            }
-            | Params::OrderedHistgrowth {
-                count,
-                positive_list,
-                negative_list,
-                groupby,
-                groupby_sample,
-                groupby_haplotype,
-                ..
-            }
-            | Params::Table {
-                count,
-                positive_list,
-                negative_list,
-                groupby,
-                groupby_sample,
-                groupby_haplotype,
-                ..
-            } => {
-                let groups = AbacusAuxilliary::load_groups(
-                    groupby,
-                    *groupby_haplotype,
-                    *groupby_sample,
-                    graph_aux,
-                )?;
-                let include_coords = AbacusAuxilliary::complement_with_group_assignments(
-                    AbacusAuxilliary::load_coord_list(positive_list)?,
-                    &groups,
-                )?;
-                let exclude_coords = AbacusAuxilliary::complement_with_group_assignments(
-                    AbacusAuxilliary::load_coord_list(negative_list)?,
-                    &groups,
-                )?;
+        let (args, count) = match (params.abacus_args(), params.count()) {
+            (Some(args), Some(count)) => (args, count),
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "cannot produce AbausData from other Param items",
+                ))
+            }
+        };
+        let AbacusArgs {
+            graph_args,
+            group_args,
+            positive_list,
+            negative_list,
+            subsample,
+            seed,
+            depth_tag,
+            depth_min,
+            depth_max,
+            reference,
+            relative_to,
+            min_path_length,
+            ..
+        } = args;
+        let mut groups = AbacusAuxilliary::load_groups(
+            &group_args.groupby,
+            group_args.groupby_haplotype,
+            group_args.groupby_sample,
+            group_args.normalize_group_names,
+            graph_aux,
+        )?;
+        let include_coords = AbacusAuxilliary::complement_with_group_assignments(
+            AbacusAuxilliary::load_coord_list(positive_list)?,
+            &groups,
+        )?;
+        let exclude_coords = AbacusAuxilliary::complement_with_group_assignments(
+            AbacusAuxilliary::load_coord_list(negative_list)?,
+            &groups,
+        )?;
 
-                let order = if let Params::OrderedHistgrowth { order, .. } = params {
-                    let maybe_order = AbacusAuxilliary::complement_with_group_assignments(
-                        AbacusAuxilliary::load_coord_list(order)?,
-                        &groups,
-                    )?;
-                    if let Some(o) = &maybe_order {
-                        // if order is given, check that it comprises all included coords
-                        let all_included_paths: Vec<PathSegment> = match &include_coords {
-                            None => {
-                                let exclude: HashSet<&PathSegment> = match &exclude_coords {
-                                    Some(e) => e.iter().collect(),
-                                    None => HashSet::new(),
-                                };
-                                graph_aux
-                                    .path_segments
-                                    .iter()
-                                    .filter_map(|x| {
-                                        if !exclude.contains(x) {
-                                            Some(x.clear_coords())
-                                        } else {
-                                            None
-                                        }
-                                    })
-                                    .collect()
-                            }
-                            Some(include) => include.iter().map(|x| x.clear_coords()).collect(),
-                        };
-                        let order_set: HashSet<&PathSegment> = HashSet::from_iter(o.iter());
-
-                        for p in all_included_paths.iter() {
-                            if !order_set.contains(p) {
-                                let msg = format!(
-                                    "order list does not contain information about path {}",
-                                    p
-                                );
-                                log::error!("{}", &msg);
-                                // let's not be that harsh, shall we?
-                                //                                return Err(std::io::Error::new(
-                                //                                    std::io::ErrorKind::InvalidData,
-                                //                                    msg,
-                                //                                ));
-                            }
-                        }
+        // subset coordinates of the reference path are kept apart: they are translated
+        // into the set of items that the reference visits within these coordinates
+        let reference = match reference.as_ref().or_else(|| relative_to.as_ref()) {
+            None => None,
+            Some(r) => {
+                let r = PathSegment::from_str(r).clear_coords();
+                if !groups.contains_key(&r) {
+                    let msg = format!("reference path {} not found in graph", &r);
+                    log::error!("{}", &msg);
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+                }
+                groups.remove(&r);
+                Some(r)
+            }
+        };
+        let (include_coords, reference_coords) = match (&reference, include_coords) {
+            (Some(r), Some(coords)) => {
+                let (ref_coords, other): (Vec<PathSegment>, Vec<PathSegment>) =
+                    coords.into_iter().partition(|x| &x.clear_coords() == r);
+                (
+                    if other.is_empty() { None } else { Some(other) },
+                    if ref_coords.is_empty() {
+                        None
+                    } else {
+                        Some(ref_coords)
+                    },
+                )
+            }
+            (_, coords) => (coords, None),
+        };
 
-                        // check that groups are not scrambled in include
-                        let mut visited: HashSet<&str> = HashSet::new();
-                        let mut cur: &str = groups.get(&o[0]).unwrap();
-                        for p in o.iter() {
-                            let g: &str = groups.get(p).unwrap();
-                            if cur != g && !visited.insert(g) {
-                                let msg = format!("order of paths contains fragmented groups: path {} belongs to group that is interspersed by one or more other groups", p);
-                                log::error!("{}", &msg);
-                                return Err(std::io::Error::new(
-                                    std::io::ErrorKind::InvalidData,
-                                    msg,
-                                ));
-                            }
-                            cur = g;
-                        }
-                    }
-                    maybe_order
+        let include_coords = if *min_path_length > 0 {
+            AbacusAuxilliary::drop_short_paths(
+                include_coords,
+                &groups,
+                *min_path_length,
+                &mut io::open_gfa(&graph_args.gfa_file, &graph_args.paths_file)?,
+                graph_aux,
+            )?
+        } else {
+            include_coords
+        };
+
+        let include_coords = match subsample {
+            Some(fraction) => AbacusAuxilliary::subsample_groups(
+                include_coords,
+                &groups,
+                *fraction,
+                *seed,
+                graph_aux,
+            )?,
+            None => include_coords,
+        };
+
+        let (order, order_weights) = if let Params::OrderedHistgrowth {
+            order, order_paf, ..
+        } = params
+        {
+            if !order.is_empty() && !order_paf.is_empty() {
+                let msg =
+                    "at most one of order and order-paf can be set at once, but both are given";
+                log::error!("{}", &msg);
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+            }
+            let weighted_order = if order_paf.is_empty() {
+                AbacusAuxilliary::load_weighted_order(order)?
+            } else {
+                None
+            };
+            let order_weights = match &weighted_order {
+                None => None,
+                Some(o) => Some(AbacusAuxilliary::group_weights(o, &groups)?),
+            };
+            let maybe_order = AbacusAuxilliary::complement_with_group_assignments(
+                if let Some(o) = weighted_order {
+                    Some(o.into_iter().map(|(p, _)| p).collect())
+                } else if order_paf.is_empty() {
+                    AbacusAuxilliary::load_coord_list(order)?
                 } else {
-                    None
+                    AbacusAuxilliary::load_paf_order(order_paf)?
+                }
+                // the reference path is not part of the order
+                .map(|o| {
+                    o.into_iter()
+                        .filter(|x| reference.as_ref() != Some(&x.clear_coords()))
+                        .collect()
+                }),
+                &groups,
+            )?;
+            if let Some(o) = &maybe_order {
+                // if order is given, check that it comprises all included coords
+                let all_included_paths: Vec<PathSegment> = match &include_coords {
+                    None => {
+                        let exclude: HashSet<&PathSegment> = match &exclude_coords {
+                            Some(e) => e.iter().collect(),
+                            None => HashSet::new(),
+                        };
+                        graph_aux
+                            .path_segments
+                            .iter()
+                            .filter_map(|x| {
+                                if !exclude.contains(x)
+                                    && !exclude.contains(&x.clear_coords())
+                                    && groups.contains_key(&x.clear_coords())
+                                {
+                                    Some(x.clear_coords())
+                                } else {
+                                    None
+                                }
+                            })
+                            .collect()
+                    }
+                    Some(include) => include.iter().map(|x| x.clear_coords()).collect(),
                 };
+                let order_set: HashSet<&PathSegment> = HashSet::from_iter(o.iter());
 
-                Ok(AbacusAuxilliary {
-                    count: count.clone(),
-                    groups: groups,
-                    include_coords: include_coords,
-                    exclude_coords: exclude_coords,
-                    order: order,
-                })
+                for p in all_included_paths.iter() {
+                    if !order_set.contains(p) {
+                        let msg =
+                            format!("order list does not contain information about path {}", p);
+                        log::error!("{}", &msg);
+                        // let's not be that harsh, shall we?
+                        //                                return Err(std::io::Error::new(
+                        //                                    std::io::ErrorKind::InvalidData,
+                        //                                    msg,
+                        //                                ));
+                    }
+                }
+
+                // check that groups are not scrambled in include
+                let mut visited: HashSet<&str> = HashSet::new();
+                let mut cur: &str = groups.get(&o[0]).unwrap();
+                for p in o.iter() {
+                    let g: &str = groups.get(p).unwrap();
+                    if cur != g && !visited.insert(g) {
+                        let msg = format!("order of paths contains fragmented groups: path {} belongs to group that is interspersed by one or more other groups", p);
+                        log::error!("{}", &msg);
+                        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+                    }
+                    cur = g;
+                }
             }
-            _ => Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "cannot produce AbausData from other Param items",
-            )),
-        }
+            (maybe_order, order_weights)
+        } else {
+            (None, None)
+        };
+
+        // subsampling drops entire groups, which must not remain in the order as empty groups
+        let order = match (order, &include_coords) {
+            (Some(o), Some(include)) if subsample.is_some() => {
+                let kept: HashSet<&String> = include
+                    .iter()
+                    .filter_map(|x| groups.get(&x.clear_coords()))
+                    .collect();
+                Some(
+                    o.into_iter()
+                        .filter(|x| {
+                            groups
+                                .get(&x.clear_coords())
+                                .map_or(false, |g| kept.contains(g))
+                        })
+                        .collect(),
+                )
+            }
+            (o, _) => o,
+        };
+
+        let depth_range = if depth_tag.is_empty() {
+            if depth_min.is_some() || depth_max.is_some() {
+                let msg = "depth-min/depth-max require depth-tag to be set";
+                log::error!("{}", &msg);
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+            }
+            None
+        } else {
+            if count == CountType::Edge {
+                let msg = "depth-based filtering is not supported when counting edges";
+                log::error!("{}", &msg);
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+            }
+            Some((
+                depth_min.unwrap_or(f64::NEG_INFINITY),
+                depth_max.unwrap_or(f64::INFINITY),
+            ))
+        };
+
+        Ok(AbacusAuxilliary {
+            count: count,
+            groups: groups,
+            include_coords: include_coords,
+            exclude_coords: exclude_coords,
+            order: order,
+            order_weights: order_weights,
+            depth_range: depth_range,
+            reference: reference,
+            reference_coords: reference_coords,
+            relative_to_reference: relative_to.is_some(),
+            multiplicity: if let Params::Hist { multiplicity, .. } = params {
+                *multiplicity
+            } else {
+                false
+            },
+            shards: None,
+        })
     }
 
     fn complement_with_group_assignments(
@@ -213,19 +344,254 @@ impl AbacusAuxilliary {
         }
     }
 
+    fn drop_short_paths<R: std::io::Read>(
+        include_coords: Option<Vec<PathSegment>>,
+        groups: &HashMap<PathSegment, String>,
+        min_path_length: usize,
+        data: &mut std::io::BufReader<R>,
+        graph_aux: &GraphAuxilliary,
+    ) -> Result<Option<Vec<PathSegment>>, std::io::Error> {
+        log::info!("measuring length of paths/walks");
+        // fragments of the same path (e.g., walks with coordinates) add up to its length
+        let mut path_lens: HashMap<PathSegment, usize> = HashMap::default();
+        for (path_seg, items) in graph_aux.path_segments.iter().zip(io::parse_gfa_path_items(
+            data,
+            graph_aux,
+            CountType::Node,
+        )?) {
+            *path_lens.entry(path_seg.clear_coords()).or_insert(0) += items
+                .into_iter()
+                .map(|v| graph_aux.node_len(&ItemId(v)) as usize)
+                .sum::<usize>();
+        }
+
+        let short: HashSet<&PathSegment> = path_lens
+            .iter()
+            .filter_map(|(p, l)| {
+                if *l < min_path_length && groups.contains_key(p) {
+                    Some(p)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        log::info!(
+            "dropped {} paths/walks shorter than {}bp",
+            short.len(),
+            min_path_length
+        );
+        if short.is_empty() {
+            return Ok(include_coords);
+        }
+        log::debug!("dropped paths: {:?}", &short);
+
+        // the filter operates on the paths that would be included otherwise
+        let paths: Vec<PathSegment> = match include_coords {
+            Some(coords) => coords,
+            None => graph_aux
+                .path_segments
+                .iter()
+                .map(|x| x.clear_coords())
+                .filter(|x| groups.contains_key(x))
+                .unique()
+                .collect(),
+        };
+        let paths: Vec<PathSegment> = paths
+            .into_iter()
+            .filter(|p| !short.contains(&p.clear_coords()))
+            .collect();
+        if paths.is_empty() {
+            let msg = format!(
+                "no path/walk is at least {}bp long, nothing left to count",
+                min_path_length
+            );
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+        }
+        Ok(Some(paths))
+    }
+
+    fn subsample_groups(
+        include_coords: Option<Vec<PathSegment>>,
+        groups: &HashMap<PathSegment, String>,
+        fraction: f64,
+        seed: Option<u64>,
+        graph_aux: &GraphAuxilliary,
+    ) -> Result<Option<Vec<PathSegment>>, std::io::Error> {
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            let msg = format!(
+                "subsample fraction must be within (0, 1], but is {}",
+                fraction
+            );
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+        }
+
+        // subsampling operates on the paths that would be included otherwise
+        let paths: Vec<PathSegment> = match include_coords {
+            Some(coords) => coords,
+            None => graph_aux
+                .path_segments
+                .iter()
+                .map(|x| x.clear_coords())
+                .filter(|x| groups.contains_key(x))
+                .collect(),
+        };
+        let group_of = |p: &PathSegment| {
+            groups
+                .get(&p.clear_coords())
+                .cloned()
+                .unwrap_or_else(|| p.id())
+        };
+
+        // sort group names so that the sample only depends on the seed
+        let mut all_groups: Vec<String> = paths.iter().map(group_of).collect();
+        all_groups.sort();
+        all_groups.dedup();
+
+        let k = usize::max(1, (fraction * all_groups.len() as f64).round() as usize);
+        let mut rng = match seed {
+            Some(s) => StdRng::seed_from_u64(s),
+            None => StdRng::from_entropy(),
+        };
+        let kept: HashSet<String> = all_groups.choose_multiple(&mut rng, k).cloned().collect();
+        log::info!(
+            "subsampling kept {} out of {} groups",
+            kept.len(),
+            all_groups.len()
+        );
+        log::debug!("kept groups: {:?}", &kept);
+
+        Ok(Some(
+            paths
+                .into_iter()
+                .filter(|p| kept.contains(&group_of(p)))
+                .collect(),
+        ))
+    }
+
     fn load_coord_list(file_name: &str) -> Result<Option<Vec<PathSegment>>, std::io::Error> {
         Ok(if file_name.is_empty() {
             None
         } else {
             log::info!("loading coordinates from {}", file_name);
-            let mut data = std::io::BufReader::new(fs::File::open(file_name)?);
-            let coords = io::parse_bed(&mut data);
+            let mut data = open_file(file_name)?;
+            let name = file_name.strip_suffix(".gz").unwrap_or(file_name);
+            let coords = if name.ends_with(".gff") || name.ends_with(".gff3") {
+                io::parse_gff(&mut data)?
+            } else {
+                io::parse_bed(&mut data)
+            };
             log::debug!("loaded {} coordinates", coords.len());
             Some(coords)
         })
     }
 
-    fn load_groups(
+    fn load_weighted_order(
+        file_name: &str,
+    ) -> Result<Option<Vec<(PathSegment, f64)>>, std::io::Error> {
+        if file_name.is_empty() {
+            return Ok(None);
+        }
+        let mut data = open_file(file_name)?;
+        let order = io::parse_weighted_order(&mut data)?;
+        if let Some(o) = &order {
+            log::info!(
+                "loaded order of {} weighted paths/groups from {}",
+                o.len(),
+                file_name
+            );
+        }
+        Ok(order)
+    }
+
+    fn group_weights(
+        weighted_order: &[(PathSegment, f64)],
+        groups: &HashMap<PathSegment, String>,
+    ) -> Result<HashMap<String, f64>, std::io::Error> {
+        // entries of the order list are either paths or group identifiers
+        let mut res: HashMap<String, f64> = HashMap::default();
+        for (p, w) in weighted_order {
+            let g = groups
+                .get(&p.clear_coords())
+                .cloned()
+                .unwrap_or_else(|| p.id());
+            match res.get(&g) {
+                Some(v) if v != w => {
+                    let msg = format!(
+                        "order list assigns conflicting weights {} and {} to group {}",
+                        v, w, g
+                    );
+                    log::error!("{}", &msg);
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+                }
+                _ => {
+                    res.insert(g, *w);
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    fn load_paf_order(file_name: &str) -> Result<Option<Vec<PathSegment>>, std::io::Error> {
+        log::info!("loading order from alignments in {}", file_name);
+        let mut data = open_file(file_name)?;
+        let order = io::parse_paf_order(&mut data)?;
+        log::debug!("loaded order of {} query sequences", order.len());
+        Ok(Some(order))
+    }
+
+    pub fn load_groups(
+        file_name: &str,
+        groupby_haplotype: bool,
+        groupby_sample: bool,
+        normalize_group_names: bool,
+        graph_aux: &GraphAuxilliary,
+    ) -> Result<HashMap<PathSegment, String>, std::io::Error> {
+        let mut groups = AbacusAuxilliary::derive_groups(
+            file_name,
+            groupby_haplotype,
+            groupby_sample,
+            graph_aux,
+        )?;
+
+        // group names that differ only by case or surrounding whitespace likely refer to the same
+        // group; they are collected in the order in which their paths occur in the graph
+        let mut variants: HashMap<String, Vec<&String>> = HashMap::default();
+        for path in graph_aux.path_segments.iter() {
+            if let Some(group) = groups.get(&path.clear_coords()) {
+                let names = variants
+                    .entry(group.trim().to_lowercase())
+                    .or_insert_with(Vec::new);
+                if !names.contains(&group) {
+                    names.push(group);
+                }
+            }
+        }
+        let mut rename: HashMap<String, String> = HashMap::default();
+        for names in variants.values().filter(|x| x.len() > 1) {
+            let names_str = names.iter().map(|x| format!("\"{}\"", x)).join(", ");
+            if normalize_group_names {
+                log::info!("merging groups {} into group \"{}\"", names_str, names[0]);
+                for name in names[1..].iter() {
+                    rename.insert(name.to_string(), names[0].to_string());
+                }
+            } else {
+                warn_or_fail(graph_aux.strict, &format!(
+                    "group names {} differ only by case or surrounding whitespace, but are treated as different groups; use normalize-group-names to merge them",
+                    names_str
+                ))?;
+            }
+        }
+        for group in groups.values_mut() {
+            if let Some(name) = rename.get(group) {
+                *group = name.clone();
+            }
+        }
+        Ok(groups)
+    }
+
+    fn derive_groups(
         file_name: &str,
         groupby_haplotype: bool,
         groupby_sample: bool,
@@ -254,7 +620,7 @@ impl AbacusAuxilliary {
                 .collect())
         } else if !file_name.is_empty() {
             log::info!("loading groups from {}", file_name);
-            let mut data = std::io::BufReader::new(fs::File::open(file_name)?);
+            let mut data = open_file(file_name)?;
             let group_assignments = io::parse_groups(&mut data)?;
             let mut path_to_group = HashMap::default();
             for (i, (path, group)) in group_assignments.into_iter().enumerate() {
@@ -277,6 +643,31 @@ impl AbacusAuxilliary {
             }
             log::debug!("loaded {} group assignments", path_to_group.len());
 
+            // paths of the group file that are not part of the graph are most likely typos
+            let graph_paths: HashSet<PathSegment> = graph_aux
+                .path_segments
+                .iter()
+                .map(|x| x.clear_coords())
+                .collect();
+            let mut unknown: Vec<String> = path_to_group
+                .keys()
+                .filter(|x| !graph_paths.contains(x))
+                .map(|x| x.to_string())
+                .collect();
+            if !unknown.is_empty() {
+                unknown.sort();
+                warn_or_fail(
+                    graph_aux.strict,
+                    &format!(
+                    "{} of {} paths listed in group file {} match no path/walk of the graph: {}",
+                    unknown.len(),
+                    path_to_group.len(),
+                    file_name,
+                    unknown.join(", ")
+                ),
+                )?;
+            }
+
             // augment the group assignments with yet unassigned path segments
             graph_aux.path_segments.iter().for_each(|x| {
                 let path = x.clear_coords();
@@ -305,6 +696,9 @@ impl AbacusAuxilliary {
         let mut group_to_paths: HashMap<&'a str, Vec<(ItemIdSize, &'a str)>> = HashMap::default();
 
         for (i, p) in path_segments.into_iter().enumerate() {
+            if self.is_reference(p) {
+                continue;
+            }
             let group: &'a str = self.groups.get(&p.clear_coords()).unwrap();
             group_to_paths
                 .entry(group)
@@ -323,7 +717,17 @@ impl AbacusAuxilliary {
             };
             path_segments
                 .into_iter()
-                .filter_map(|x| if !exclude.contains(x) { Some(x) } else { None })
+                .filter_map(|x| {
+                    // walks carry their coordinates, but may be excluded by their plain ID
+                    if !exclude.contains(x)
+                        && !exclude.contains(&x.clear_coords())
+                        && !self.is_reference(x)
+                    {
+                        Some(x)
+                    } else {
+                        None
+                    }
+                })
                 .collect::<Vec<&PathSegment>>()
         };
         Ok(order
@@ -337,6 +741,13 @@ impl AbacusAuxilliary {
             .concat())
     }
 
+    pub fn is_reference(&self, path_seg: &PathSegment) -> bool {
+        match &self.reference {
+            Some(r) => r == &path_seg.clear_coords(),
+            None => false,
+        }
+    }
+
     pub fn count_groups(&self) -> usize {
         HashSet::<&String>::from_iter(self.groups.values()).len()
     }
@@ -349,6 +760,8 @@ pub struct AbacusByTotal {
     pub uncovered_bps: HashMap<ItemIdSize, usize>,
     pub groups: Vec<String>,
     pub graph_aux: GraphAuxilliary,
+    // whether countable holds the number of traversals rather than that of covering groups
+    pub multiplicity: bool,
 }
 
 impl AbacusByTotal {
@@ -359,7 +772,7 @@ impl AbacusByTotal {
     ) -> Result<Self, std::io::Error> {
         log::info!("parsing path + walk sequences");
         let (item_table, exclude_table, subset_covered_bps) =
-            io::parse_gfa_itemcount(data, &abacus_aux, &graph_aux);
+            io::parse_gfa_itemcount(data, &abacus_aux, &graph_aux)?;
         log::info!("counting abacus entries..");
         // first element in countable is the "zero" element--which should be ignored in
         // counting
@@ -382,6 +795,7 @@ impl AbacusByTotal {
                 &exclude_table,
                 path_id,
                 groups.len() as ItemIdSize - 1,
+                abacus_aux.multiplicity,
             );
         }
 
@@ -391,9 +805,124 @@ impl AbacusByTotal {
             uncovered_bps: quantify_uncovered_bps(&exclude_table, &subset_covered_bps, &graph_aux),
             groups: groups,
             graph_aux: graph_aux,
+            multiplicity: abacus_aux.multiplicity,
+        })
+    }
+
+    // straightforward counting that bypasses the item table, used to validate the latter
+    pub fn from_gfa_naive<R: std::io::Read>(
+        data: &mut std::io::BufReader<R>,
+        abacus_aux: AbacusAuxilliary,
+        graph_aux: GraphAuxilliary,
+    ) -> Result<Self, std::io::Error> {
+        if abacus_aux.include_coords.is_some()
+            || abacus_aux.exclude_coords.is_some()
+            || abacus_aux.depth_range.is_some()
+            || abacus_aux.reference.is_some()
+        {
+            let msg = "naive counting cannot be combined with subset, subsample, exclude, depth, or reference settings";
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+        }
+
+        log::info!("parsing path + walk sequences");
+        let paths = io::parse_gfa_path_items(data, &graph_aux, abacus_aux.count)?;
+        log::info!("counting abacus entries naively..");
+
+        let mut counter: HashMap<ItemIdSize, CountSize> = HashMap::default();
+        let mut groups: Vec<String> = Vec::new();
+        let mut group_items: HashSet<ItemIdSize> = HashSet::new();
+        for (path_id, group_id) in abacus_aux.get_path_order(&graph_aux.path_segments)? {
+            if groups.last().map(|g| g != group_id).unwrap_or(true) {
+                for id in group_items.drain() {
+                    *counter.entry(id).or_insert(0) += 1;
+                }
+                groups.push(group_id.to_string());
+            }
+            group_items.extend(paths[path_id as usize].iter());
+        }
+        for id in group_items.drain() {
+            *counter.entry(id).or_insert(0) += 1;
+        }
+
+        let mut countable: Vec<CountSize> =
+            vec![0; graph_aux.number_of_items(&abacus_aux.count) + 1];
+        countable[0] = CountSize::MAX;
+        for (id, c) in counter {
+            countable[id as usize] = c;
+        }
+
+        Ok(Self {
+            count: abacus_aux.count,
+            countable: countable,
+            uncovered_bps: HashMap::default(),
+            groups: groups,
+            graph_aux: graph_aux,
+            multiplicity: false,
         })
     }
 
+    pub fn accumulate(&mut self, file_name: &str) -> Result<(), std::io::Error> {
+        if self.count != CountType::Node {
+            let msg =
+                "accumulating coverage over several runs is only supported when counting nodes";
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+        }
+
+        // nodes of previous runs that are not part of this graph are appended to countable, so
+        // that they are still considered in the histogram
+        let n = self.graph_aux.number_of_nodes();
+        let mut extra: Vec<Vec<u8>> = Vec::new();
+        if std::path::Path::new(file_name).exists() {
+            log::info!("loading accumulated coverage from {}", file_name);
+            let mut data = buffered(fs::File::open(file_name)?);
+            let (groups, coverage) = io::parse_coverage_state(&mut data)?;
+            for group in groups.iter() {
+                if self.groups.contains(group) {
+                    let msg = format!(
+                        "group {} has already been counted in a previous run recorded in {}",
+                        group, file_name
+                    );
+                    log::error!("{}", &msg);
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+                }
+            }
+            for (node, c) in coverage {
+                match self.graph_aux.node_id(&node) {
+                    Some(id) => self.countable[id.0 as usize] += c,
+                    None => {
+                        extra.push(node);
+                        self.countable.push(c);
+                    }
+                }
+            }
+            log::info!(
+                "added coverage of {} groups from previous runs, {} of their nodes are not part of the graph",
+                groups.len(),
+                extra.len()
+            );
+            self.groups.splice(0..0, groups);
+        }
+
+        log::info!("writing accumulated coverage to {}", file_name);
+        let id2node = self.graph_aux.id2node();
+        let mut out = BufWriter::new(fs::File::create(file_name)?);
+        writeln!(out, "groups\t{}", self.groups.join("\t"))?;
+        for (i, c) in self.countable.iter().enumerate().skip(1) {
+            if *c > 0 {
+                if i <= n {
+                    writeln!(out, "{}\t{}", &id2node[i], c)?;
+                } else {
+                    out.write_all(&extra[i - n - 1])?;
+                    writeln!(out, "\t{}", c)?;
+                }
+            }
+        }
+        out.flush()?;
+        Ok(())
+    }
+
     fn coverage(
         countable: &mut Vec<CountSize>,
         last: &mut Vec<ItemIdSize>,
@@ -401,18 +930,24 @@ impl AbacusByTotal {
         exclude_table: &Option<ActiveTable>,
         path_id: ItemIdSize,
         group_id: ItemIdSize,
+        multiplicity: bool,
     ) {
         let countable_ptr = Wrap(countable);
         let last_ptr = Wrap(last);
 
         // Parallel node counting
-        (0..SIZE_T).into_par_iter().for_each(|i| {
+        (0..item_table.shards).into_par_iter().for_each(|i| {
             let start = item_table.id_prefsum[i][path_id as usize] as usize;
             let end = item_table.id_prefsum[i][path_id as usize + 1] as usize;
             for j in start..end {
                 let sid = item_table.items[i][j] as usize;
                 unsafe {
-                    if last[sid] != group_id
+                    // paths of the same group are processed consecutively, so last[sid] ensures
+                    // that an item shared by several paths of a group is counted only once for
+                    // that group, irrespective of the orientation in which the paths traverse it
+                    // (for bp, partially covered nodes are handled in node coordinates, see
+                    // update_tables), unless each traversal is to be counted
+                    if (multiplicity || last[sid] != group_id)
                         && (exclude_table.is_none() || !exclude_table.as_ref().unwrap().items[sid])
                     {
                         (*countable_ptr.0)[sid] += 1;
@@ -424,10 +959,174 @@ impl AbacusByTotal {
     }
 
     //Why &self and not self? we could destroy abacus at this point.
+    pub fn overcounted_items(&self) -> Vec<usize> {
+        // IDs of items whose coverage exceeds the number of groups; the first entry of countable
+        // is ignored. Traversal counts may exceed the number of groups by design
+        if self.multiplicity {
+            return Vec::new();
+        }
+        self.countable
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, c)| **c as usize > self.groups.len())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn write_item_lists(&self, prefix: &str) -> Result<(), std::io::Error> {
+        // one file per coverage level, each listing the IDs of the items of that coverage in the
+        // same way as the histogram counts them
+        let mut levels: Vec<Vec<usize>> = vec![Vec::new(); self.hist_len()];
+        let alleles = if self.count == CountType::Allele {
+            self.graph_aux.bubble_alleles()
+        } else {
+            Arc::default()
+        };
+        for (i, cov) in self.countable.iter().enumerate().skip(1) {
+            if (*cov as usize) < levels.len()
+                && (self.count != CountType::Allele || alleles.contains(&(i as ItemIdSize)))
+            {
+                levels[*cov as usize].push(i);
+            }
+        }
+
+        let id2node = self.graph_aux.id2node();
+        let mut id2edge: Vec<Option<&Edge>> = Vec::new();
+        if self.count == CountType::Edge {
+            id2edge = vec![None; self.graph_aux.number_of_edges() + 1];
+            for (edge, id) in self.graph_aux.edge2id.as_ref().unwrap().iter() {
+                id2edge[id.0 as usize] = Some(edge);
+            }
+        }
+        for (c, items) in levels.iter().enumerate() {
+            let file_name = format!("{}.{}.txt", prefix, c);
+            log::debug!(
+                "writing {} {}s of coverage {} to {}",
+                items.len(),
+                self.count,
+                c,
+                file_name
+            );
+            let mut out = BufWriter::new(fs::File::create(&file_name)?);
+            for &i in items {
+                match id2edge.get(i) {
+                    Some(Some(edge)) => {
+                        writeln!(out, "{}", self.graph_aux.edge_name(&id2node, edge))?
+                    }
+                    _ => writeln!(out, "{}", &id2node[i])?,
+                }
+            }
+            out.flush()?;
+        }
+        log::info!(
+            "wrote {}s of coverage 0 to {} to {}.<coverage>.txt",
+            self.count,
+            levels.len() - 1,
+            prefix
+        );
+        Ok(())
+    }
+
+    pub fn hotspots_to_tsv<W: Write>(
+        &self,
+        top: usize,
+        score: HotspotScore,
+        out: &mut BufWriter<W>,
+    ) -> Result<(), std::io::Error> {
+        // both scores are symmetric around, and maximal at, half the number of groups
+        let n = self.groups.len() as f64;
+        let label = match score {
+            HotspotScore::Variance => "variance",
+            HotspotScore::Entropy => "entropy",
+        };
+        let score_of = |cov: CountSize| {
+            let p = f64::min(cov as f64 / n, 1.0);
+            match score {
+                HotspotScore::Variance => p * (1.0 - p),
+                HotspotScore::Entropy => {
+                    if p <= 0.0 || p >= 1.0 {
+                        0.0
+                    } else {
+                        -p * p.log2() - (1.0 - p) * (1.0 - p).log2()
+                    }
+                }
+            }
+        };
+        let alleles = if self.count == CountType::Allele {
+            self.graph_aux.bubble_alleles()
+        } else {
+            Arc::default()
+        };
+        let mut items: Vec<(f64, usize)> = self
+            .countable
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(i, _)| {
+                self.count != CountType::Allele || alleles.contains(&(*i as ItemIdSize))
+            })
+            .map(|(i, cov)| (score_of(*cov), i))
+            .collect();
+        // ties are broken by item ID so that the output is deterministic
+        items.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap().then(a.1.cmp(&b.1)));
+        items.truncate(top);
+        log::info!(
+            "reporting {} {}s with highest {} across {} groups",
+            items.len(),
+            self.count,
+            label,
+            self.groups.len()
+        );
+
+        let id2node = self.graph_aux.id2node();
+        let mut id2edge: Vec<Option<&Edge>> = Vec::new();
+        if self.count == CountType::Edge {
+            id2edge = vec![None; self.graph_aux.number_of_edges() + 1];
+            for (edge, id) in self.graph_aux.edge2id.as_ref().unwrap().iter() {
+                id2edge[id.0 as usize] = Some(edge);
+            }
+        }
+        writeln!(
+            out,
+            "{}\tcoverage\t{}",
+            if self.count == CountType::Edge {
+                "edge"
+            } else {
+                "node"
+            },
+            label
+        )?;
+        for (x, i) in items {
+            let name = match id2edge.get(i) {
+                Some(Some(edge)) => self.graph_aux.edge_name(&id2node, edge),
+                _ => id2node[i].clone(),
+            };
+            writeln!(out, "{}\t{}\t{:.4}", name, self.countable[i], x)?;
+        }
+        Ok(())
+    }
+
+    fn hist_len(&self) -> usize {
+        // when counting traversals, coverage is bounded by the most traversed item rather than
+        // the number of groups
+        let max_cov = if self.multiplicity {
+            self.countable
+                .iter()
+                .skip(1)
+                .max()
+                .map(|x| *x as usize)
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        usize::max(self.groups.len(), max_cov) + 1
+    }
+
     pub fn construct_hist(&self) -> Vec<usize> {
         // hist must be of size = num_groups + 1; having an index that starts from 1, instead of 0,
         // makes easier the calculation in hist2pangrowth.
-        let mut hist: Vec<usize> = vec![0; self.groups.len() + 1];
+        let mut hist: Vec<usize> = vec![0; self.hist_len()];
 
         for (i, cov) in self.countable.iter().enumerate() {
             if *cov as usize >= hist.len() {
@@ -441,10 +1140,24 @@ impl AbacusByTotal {
         hist
     }
 
-    pub fn construct_hist_bps(&self) -> Vec<usize> {
-        // hist must be of size = num_groups + 1; having an index that starts from 1, instead of 0,
+    pub fn construct_hist_alleles(&self) -> Vec<usize> {
+        // only nodes that constitute alleles of simple bubbles are counted
+        let alleles = self.graph_aux.bubble_alleles();
+        log::info!("found {} alleles in simple bubbles", alleles.len());
+        let mut hist: Vec<usize> = vec![0; self.hist_len()];
+        for &id in alleles.iter() {
+            let cov = self.countable[id as usize] as usize;
+            if cov < hist.len() {
+                hist[cov] += 1;
+            }
+        }
+        hist
+    }
+
+    pub fn construct_hist_bps(&self) -> Vec<usize> {
+        // hist must be of size = num_groups + 1; having an index that starts from 1, instead of 0,
         // makes easier the calculation in hist2pangrowth.
-        let mut hist: Vec<usize> = vec![0; self.groups.len() + 1];
+        let mut hist: Vec<usize> = vec![0; self.hist_len()];
         for (id, cov) in self.countable.iter().enumerate() {
             if *cov as usize >= hist.len() {
                 if id != 0 {
@@ -471,6 +1184,9 @@ pub struct AbacusByGroup {
     pub r: Vec<usize>,
     pub v: Option<Vec<CountSize>>,
     pub c: Vec<GroupSize>,
+    // if set, values of the table in the same layout as v, but with each traversal weighted by
+    // the inverse length of the traversing path
+    pub path_fractions: Option<Vec<f64>>,
     pub uncovered_bps: HashMap<ItemIdSize, usize>,
     pub groups: Vec<String>,
     pub graph_aux: GraphAuxilliary,
@@ -482,10 +1198,11 @@ impl AbacusByGroup {
         abacus_aux: AbacusAuxilliary,
         graph_aux: GraphAuxilliary,
         report_values: bool,
+        path_fractions: bool,
     ) -> Result<Self, std::io::Error> {
         log::info!("parsing path + walk sequences");
         let (item_table, exclude_table, subset_covered_bps) =
-            io::parse_gfa_itemcount(data, &abacus_aux, &graph_aux);
+            io::parse_gfa_itemcount(data, &abacus_aux, &graph_aux)?;
 
         let mut path_order: Vec<(ItemIdSize, GroupSize)> = Vec::new();
         let mut groups: Vec<String> = Vec::new();
@@ -512,12 +1229,25 @@ impl AbacusByGroup {
         );
         let (v, c) =
             AbacusByGroup::compute_column_values(&item_table, &path_order, &r, report_values);
+        let path_fractions = if path_fractions {
+            Some(AbacusByGroup::compute_path_fractions(
+                &item_table,
+                &path_order,
+                &r,
+                &c,
+                abacus_aux.count,
+                &graph_aux,
+            ))
+        } else {
+            None
+        };
 
         Ok(Self {
             count: abacus_aux.count,
             r: r,
             v: v,
             c: c,
+            path_fractions: path_fractions,
             uncovered_bps: quantify_uncovered_bps(&exclude_table, &subset_covered_bps, &graph_aux),
             groups: groups,
             graph_aux: graph_aux,
@@ -537,7 +1267,7 @@ impl AbacusByGroup {
         let mut r: Vec<usize> = vec![0; n_items + 2];
         let r_ptr = Wrap(&mut r);
         for (path_id, group_id) in path_order {
-            (0..SIZE_T).into_par_iter().for_each(|i| {
+            (0..item_table.shards).into_par_iter().for_each(|i| {
                 let start = item_table.id_prefsum[i][*path_id as usize] as usize;
                 let end = item_table.id_prefsum[i][*path_id as usize + 1] as usize;
                 for j in start..end {
@@ -592,7 +1322,7 @@ impl AbacusByGroup {
         // group id is monotone increasing from 0 to #groups
         for (path_id, group_id) in path_order {
             let path_id_u = *path_id as usize;
-            (0..SIZE_T).into_par_iter().for_each(|i| {
+            (0..item_table.shards).into_par_iter().for_each(|i| {
                 let start = item_table.id_prefsum[i][path_id_u] as usize;
                 let end = item_table.id_prefsum[i][path_id_u + 1] as usize;
                 for j in start..end {
@@ -657,46 +1387,641 @@ impl AbacusByGroup {
         (if report_values { Some(v) } else { None }, c)
     }
 
+    fn compute_path_fractions(
+        item_table: &ItemTable,
+        path_order: &Vec<(ItemIdSize, GroupSize)>,
+        r: &Vec<usize>,
+        c: &Vec<GroupSize>,
+        count: CountType,
+        graph_aux: &GraphAuxilliary,
+    ) -> Vec<f64> {
+        // each traversal of an item contributes the inverse of the length of the traversing path
+        // (in items, or in bp when counting bp), so that each path contributes 1 in total when
+        // values are multiplied by item lengths; contributions of the paths of a group are summed
+        log::info!("computing coverage as fraction of path length..");
+        let mut res = vec![0.0; c.len()];
+        for (path_id, group_id) in path_order {
+            let p = *path_id as usize;
+            let items = (0..item_table.shards).flat_map(|i| {
+                item_table.items[i]
+                    [item_table.id_prefsum[i][p] as usize..item_table.id_prefsum[i][p + 1] as usize]
+                    .iter()
+            });
+            let len: usize = items
+                .clone()
+                .map(|&sid| {
+                    if count == CountType::Bp {
+                        graph_aux.node_len_ary[sid as usize] as usize
+                    } else {
+                        1
+                    }
+                })
+                .sum();
+            if len == 0 {
+                continue;
+            }
+            for &sid in items {
+                let (start, end) = (r[sid as usize], r[sid as usize + 1]);
+                // group IDs associated with an item are sorted
+                if let Ok(k) = c[start..end].binary_search(group_id) {
+                    res[start + k] += 1.0 / len as f64;
+                }
+            }
+        }
+        log::info!("done");
+        res
+    }
+
     // why &self and not self? we could destroy abacus at this point.
-    pub fn calc_growth(&self, t_coverage: &Threshold, t_quorum: &Threshold) -> Vec<f64> {
-        let mut res = vec![0.0; self.groups.len()];
+    pub fn overcounted_items(&self) -> Vec<usize> {
+        // IDs of items that are associated with more groups than there are; the first entry of
+        // self.r is ignored
+        (1..self.r.len() - 1)
+            .filter(|&i| self.r[i + 1] - self.r[i] > self.groups.len())
+            .collect()
+    }
+
+    pub fn sample_items(
+        &self,
+        fraction: f64,
+        seed: Option<u64>,
+    ) -> Result<Vec<usize>, std::io::Error> {
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            let msg = format!(
+                "item subsample fraction must be within (0, 1], but is {}",
+                fraction
+            );
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+        }
+        let mut rng = match seed {
+            Some(s) => StdRng::seed_from_u64(s),
+            None => StdRng::from_entropy(),
+        };
+        // the first entry of self.r is ignored
+        let items: Vec<usize> = (1..self.r.len() - 1).collect();
+        let k = usize::max(1, (fraction * items.len() as f64).round() as usize);
+        let mut res: Vec<usize> = items.choose_multiple(&mut rng, k).copied().collect();
+        res.sort_unstable();
+        log::info!(
+            "sampled {} out of {} countables for computing growth",
+            res.len(),
+            items.len()
+        );
+        Ok(res)
+    }
 
-        let c = usize::max(1, t_coverage.to_absolute(self.groups.len()));
-        let q = f64::max(0.0, t_quorum.to_relative(self.groups.len()));
+    pub fn calc_growth(
+        &self,
+        t_coverage: &Threshold,
+        t_quorum: &Threshold,
+        count: CountType,
+        items: Option<&[usize]>,
+        direction: Direction,
+        stepwise: bool,
+    ) -> Vec<f64> {
+        // count may differ from self.count only for an abacus over nodes, which can be
+        // reported in either node or bp counts
+        let n = self.groups.len();
+        // decay is the growth along the reversed order of groups, read backwards: the j-th group
+        // of the reversed order is group n - 1 - j, and the groups covering an item are visited
+        // from last to first
+        let decay = direction == Direction::Decay;
 
-        let mut it = self.r.iter().tuple_windows().enumerate();
-        // ignore first entry
-        it.next();
-        for (i, (&start, &end)) in it {
+        let c = usize::max(1, t_coverage.to_absolute(n));
+        let q = f64::max(0.0, t_quorum.to_relative(n));
+        let alleles = if count == CountType::Allele {
+            self.graph_aux.bubble_alleles()
+        } else {
+            Arc::default()
+        };
+
+        // items are processed in parallel, each thread accumulates its own growth vector, which
+        // are summed up in the end; the first entry of self.r is ignored
+        let growth = |mut res: Vec<f64>, i: usize| {
+            let start = self.r[i];
+            let end = self.r[i + 1];
             if end - start >= c {
+                let col = |k: usize| {
+                    if decay {
+                        n - 1 - self.c[end - 1 - (k - start)] as usize
+                    } else {
+                        self.c[k] as usize
+                    }
+                };
                 let mut k = start;
-                for j in self.c[start] as usize..self.groups.len() {
-                    if k < end - 1 && self.c[k + 1] as usize <= j {
+                for j in col(start)..n {
+                    if k < end - 1 && col(k + 1) <= j {
                         k += 1
                     }
-                    if k - start + 1 >= ((self.c[k] as f64 + 1.0) * q).ceil() as usize {
+                    // the item is covered by k - start + 1 of the first j + 1 groups; if stepwise,
+                    // both coverage and quorum are evaluated w.r.t. these j + 1 groups, otherwise
+                    // coverage refers to all groups (see above) and quorum to the groups up to
+                    // the last one covering the item
+                    let min_cov = if stepwise {
+                        usize::max(c, ((j as f64 + 1.0) * q).ceil() as usize)
+                    } else {
+                        ((col(k) as f64 + 1.0) * q).ceil() as usize
+                    };
+                    if k - start + 1 >= min_cov {
                         // we never need to look into the actual value in self.v, because we
                         // know it must be non-zero, which is sufficient
-                        match self.count {
-                            CountType::Node | CountType::Edge => res[j] += 1.0,
-                            CountType::Bp => {
-                                let uncovered =
-                                    self.uncovered_bps.get(&(i as ItemIdSize)).unwrap_or(&0);
-                                let covered = self.graph_aux.node_len_ary[i] as usize;
-                                if uncovered > &covered {
-                                    log::error!("oops, #uncovered bps ({}) is larger than #coverd bps ({}) for node with sid {})", &uncovered, &covered, i);
-                                } else {
-                                    res[j] += (covered - uncovered) as f64
-                                }
-                            }
-                        }
+                        res[j] += self.item_weight(i, count, &alleles);
                     }
                 }
             }
+            res
+        };
+        let sum = |mut a: Vec<f64>, b: Vec<f64>| {
+            a.iter_mut().zip(b).for_each(|(x, y)| *x += y);
+            a
+        };
+        let mut res = match items {
+            None => (1..self.r.len() - 1)
+                .into_par_iter()
+                .fold(|| vec![0.0; n], growth)
+                .reduce(|| vec![0.0; n], sum),
+            Some(items) => {
+                // growth of a sample of the items is scaled up to all items
+                let scale = (self.r.len() - 2) as f64 / items.len() as f64;
+                let mut res = items
+                    .par_iter()
+                    .copied()
+                    .fold(|| vec![0.0; n], growth)
+                    .reduce(|| vec![0.0; n], sum);
+                res.iter_mut().for_each(|x| *x *= scale);
+                res
+            }
+        };
+        if decay {
+            res.reverse();
         }
         res
     }
 
+    // contribution of the i-th item to the count
+    fn item_weight(&self, i: usize, count: CountType, alleles: &HashSet<ItemIdSize>) -> f64 {
+        match count {
+            CountType::Node | CountType::Edge => 1.0,
+            CountType::Allele => {
+                if alleles.contains(&(i as ItemIdSize)) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            CountType::Bp => {
+                let uncovered = self.uncovered_bps.get(&(i as ItemIdSize)).unwrap_or(&0);
+                let covered = self.graph_aux.node_len_ary[i] as usize;
+                if uncovered > &covered {
+                    log::error!("oops, #uncovered bps ({}) is larger than #coverd bps ({}) for node with sid {})", &uncovered, &covered, i);
+                    0.0
+                } else {
+                    (covered - uncovered) as f64
+                }
+            }
+        }
+    }
+
+    pub fn rarefy(&self, size: usize, replicates: usize, seed: Option<u64>) -> Vec<f64> {
+        let n = self.groups.len();
+        let alleles = if self.count == CountType::Allele {
+            self.graph_aux.bubble_alleles()
+        } else {
+            Arc::default()
+        };
+        let mut rng = match seed {
+            Some(s) => StdRng::seed_from_u64(s),
+            None => StdRng::from_entropy(),
+        };
+        let all_groups: Vec<GroupSize> = (0..n as GroupSize).collect();
+
+        // each replicate counts the items that are covered by at least one of the sampled groups
+        (0..replicates)
+            .map(|_| {
+                let mut sampled = vec![false; n];
+                for g in all_groups.choose_multiple(&mut rng, size) {
+                    sampled[*g as usize] = true;
+                }
+                (1..self.r.len() - 1)
+                    .into_par_iter()
+                    .filter(|i| {
+                        self.c[self.r[*i]..self.r[*i + 1]]
+                            .iter()
+                            .any(|g| sampled[*g as usize])
+                    })
+                    .map(|i| self.item_weight(i, self.count, &alleles))
+                    .sum()
+            })
+            .collect()
+    }
+
+    // draws the given number of bootstrap samples of the groups, each comprising as many groups as
+    // there are, drawn with replacement
+    pub fn bootstrap_orders(&self, replicates: usize, seed: Option<u64>) -> Vec<Vec<GroupSize>> {
+        let n = self.groups.len();
+        let mut rng = match seed {
+            Some(s) => StdRng::seed_from_u64(s),
+            None => StdRng::from_entropy(),
+        };
+        (0..replicates)
+            .map(|_| (0..n).map(|_| rng.gen_range(0..n) as GroupSize).collect())
+            .collect()
+    }
+
+    // growth along an arbitrary sequence of groups, in which groups may repeat; the coverage of an
+    // item after j steps is the number of steps among them that draw a group covering the item,
+    // i.e., a group drawn repeatedly contributes repeatedly. Thresholds are evaluated as in
+    // calc_growth, with the steps taking the place of the groups
+    pub fn calc_growth_in_order(
+        &self,
+        order: &[GroupSize],
+        t_coverage: &Threshold,
+        t_quorum: &Threshold,
+        count: CountType,
+        stepwise: bool,
+    ) -> Vec<f64> {
+        let n = order.len();
+        let c = usize::max(1, t_coverage.to_absolute(n));
+        let q = f64::max(0.0, t_quorum.to_relative(n));
+        let alleles = if count == CountType::Allele {
+            self.graph_aux.bubble_alleles()
+        } else {
+            Arc::default()
+        };
+        // steps at which each group is drawn
+        let mut steps: Vec<Vec<usize>> = vec![Vec::new(); self.groups.len()];
+        for (j, g) in order.iter().enumerate() {
+            steps[*g as usize].push(j);
+        }
+
+        let growth = |mut res: Vec<f64>, i: usize| {
+            let mut cov: Vec<usize> = self.c[self.r[i]..self.r[i + 1]]
+                .iter()
+                .flat_map(|g| steps[*g as usize].iter().copied())
+                .collect();
+            if cov.len() >= c {
+                cov.sort_unstable();
+                let mut k = 0;
+                for j in cov[0]..n {
+                    // each step draws a single group, so that steps covering the item are distinct
+                    if k < cov.len() - 1 && cov[k + 1] <= j {
+                        k += 1
+                    }
+                    let min_cov = if stepwise {
+                        usize::max(c, ((j as f64 + 1.0) * q).ceil() as usize)
+                    } else {
+                        ((cov[k] as f64 + 1.0) * q).ceil() as usize
+                    };
+                    if k + 1 >= min_cov {
+                        res[j] += self.item_weight(i, count, &alleles);
+                    }
+                }
+            }
+            res
+        };
+        // the first entry of self.r is ignored
+        (1..self.r.len() - 1)
+            .into_par_iter()
+            .fold(|| vec![0.0; n], growth)
+            .reduce(
+                || vec![0.0; n],
+                |mut a, b| {
+                    a.iter_mut().zip(b).for_each(|(x, y)| *x += y);
+                    a
+                },
+            )
+    }
+
+    pub fn attribution_to_tsv<W: Write>(
+        &self,
+        normalize_by_length: bool,
+        split_group_columns: bool,
+        out: &mut BufWriter<W>,
+    ) -> Result<(), std::io::Error> {
+        let n = self.groups.len();
+        let bps = if normalize_by_length {
+            Some(self.group_bps()?)
+        } else {
+            None
+        };
+        let alleles = if self.count == CountType::Allele {
+            self.graph_aux.bubble_alleles()
+        } else {
+            Arc::default()
+        };
+
+        // per group, the total count of traversed items and the count of those traversed by no
+        // other group; the first entry of self.r is ignored
+        let (total, private) = (1..self.r.len() - 1)
+            .into_par_iter()
+            .fold(
+                || (vec![0.0; n], vec![0.0; n]),
+                |(mut total, mut private), i| {
+                    let start = self.r[i];
+                    let end = self.r[i + 1];
+                    let w = self.item_weight(i, self.count, &alleles);
+                    for g in &self.c[start..end] {
+                        total[*g as usize] += w;
+                    }
+                    if end - start == 1 {
+                        private[self.c[start] as usize] += w;
+                    }
+                    (total, private)
+                },
+            )
+            .reduce(
+                || (vec![0.0; n], vec![0.0; n]),
+                |(mut a1, mut a2), (b1, b2)| {
+                    a1.iter_mut().zip(b1).for_each(|(x, y)| *x += y);
+                    a2.iter_mut().zip(b2).for_each(|(x, y)| *x += y);
+                    (a1, a2)
+                },
+            );
+
+        writeln!(
+            out,
+            "group{}\ttotal_{}\tshared_{}\tprivate_{}",
+            if split_group_columns {
+                "\tsample\thaplotype\tseqid"
+            } else {
+                ""
+            },
+            self.count,
+            self.count,
+            self.count
+        )?;
+        for (i, (g, (t, p))) in self
+            .groups
+            .iter()
+            .zip(total.into_iter().zip(private))
+            .enumerate()
+        {
+            let (t, p) = match &bps {
+                Some(bps) => (density(t, bps[i]), density(p, bps[i])),
+                None => (t, p),
+            };
+            if split_group_columns {
+                write!(out, "{}\t{}", g, pansn_fields(g).join("\t"))?;
+            } else {
+                write!(out, "{}", g)?;
+            }
+            writeln!(out, "\t{}\t{}\t{}", t, t - p, p)?;
+        }
+        Ok(())
+    }
+
+    pub fn contributions_to_tsv<W: Write>(
+        &self,
+        decay: bool,
+        out: &mut BufWriter<W>,
+    ) -> Result<(), std::io::Error> {
+        // each item is attributed to the first group (in the order of groups) that covers it;
+        // group IDs in c are sorted, so this is the first entry of the item's row. In decay,
+        // where groups are removed in order, an item is lost with the last group covering it
+        let n = self.groups.len();
+        let none = HashSet::new();
+        let mut new_items = vec![0usize; n];
+        let mut new_bps = vec![0.0; n];
+        for i in 1..self.r.len() - 1 {
+            if self.r[i] == self.r[i + 1] {
+                continue;
+            }
+            let g = if decay {
+                self.c[self.r[i + 1] - 1] as usize
+            } else {
+                self.c[self.r[i]] as usize
+            };
+            new_items[g] += 1;
+            if self.count != CountType::Edge {
+                new_bps[g] += self.item_weight(i, CountType::Bp, &none);
+            }
+        }
+
+        // cumulative sums run along the order of groups, or against it in decay
+        let mut cum_items = vec![0usize; n];
+        let mut cum_bps = vec![0.0; n];
+        let (mut acc_items, mut acc_bps) = (0, 0.0);
+        let order: Vec<usize> = if decay {
+            (0..n).rev().collect()
+        } else {
+            (0..n).collect()
+        };
+        for g in order {
+            acc_items += new_items[g];
+            acc_bps += new_bps[g];
+            cum_items[g] = acc_items;
+            cum_bps[g] = acc_bps;
+        }
+        if self.count == CountType::Edge {
+            writeln!(out, "group\tnew_edges\tcumulative_edges")?;
+        } else {
+            writeln!(
+                out,
+                "group\tnew_nodes\tnew_bp\tcumulative_nodes\tcumulative_bp"
+            )?;
+        }
+        for (g, group) in self.groups.iter().enumerate() {
+            if self.count == CountType::Edge {
+                writeln!(out, "{}\t{}\t{}", group, new_items[g], cum_items[g])?;
+            } else {
+                writeln!(
+                    out,
+                    "{}\t{}\t{}\t{}\t{}",
+                    group, new_items[g], new_bps[g], cum_items[g], cum_bps[g]
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn compare_to_tsv<W: Write>(
+        &self,
+        a: &str,
+        b: &str,
+        with_bp: bool,
+        out: &mut BufWriter<W>,
+    ) -> Result<(), std::io::Error> {
+        let ga = self.group_index(a)?;
+        let gb = self.group_index(b)?;
+        let with_bp = if with_bp && self.count == CountType::Edge {
+            warn_or_fail(
+                self.graph_aux.strict,
+                "edges have no length, omitting bp column",
+            )?;
+            false
+        } else {
+            with_bp
+        };
+        let alleles = if self.count == CountType::Allele {
+            self.graph_aux.bubble_alleles()
+        } else {
+            Arc::default()
+        };
+
+        // count and length of the items present only in a, only in b, and in both
+        let mut res = [(0.0, 0.0); 3];
+        let mut it = self.r.iter().tuple_windows().enumerate();
+        // ignore first entry
+        it.next();
+        for (i, (&start, &end)) in it {
+            let groups = &self.c[start..end];
+            let k = match (groups.contains(&ga), groups.contains(&gb)) {
+                (true, false) => 0,
+                (false, true) => 1,
+                (true, true) => 2,
+                (false, false) => continue,
+            };
+            let w = self.item_weight(i, self.count, &alleles);
+            res[k].0 += w;
+            if with_bp && w > 0.0 {
+                res[k].1 += self.item_weight(i, CountType::Bp, &alleles);
+            }
+        }
+
+        write!(out, "category\t{}", self.count)?;
+        if with_bp {
+            write!(out, "\tbp")?;
+        }
+        writeln!(out, "")?;
+        for (category, (c, bp)) in ["a_only", "b_only", "both"].iter().zip(res) {
+            write!(out, "{}\t{}", category, c)?;
+            if with_bp {
+                write!(out, "\t{}", bp)?;
+            }
+            writeln!(out, "")?;
+        }
+        Ok(())
+    }
+
+    pub fn item_to_tsv<W: Write>(
+        &self,
+        name: &str,
+        id: ItemIdSize,
+        out: &mut BufWriter<W>,
+    ) -> Result<(), std::io::Error> {
+        let start = self.r[id as usize];
+        let end = self.r[id as usize + 1];
+        writeln!(out, "{}\tcoverage\tgroups", self.count)?;
+        writeln!(
+            out,
+            "{}\t{}\t{}",
+            name,
+            end - start,
+            self.c[start..end]
+                .iter()
+                .map(|g| &self.groups[*g as usize][..])
+                .join(",")
+        )?;
+        Ok(())
+    }
+
+    pub fn rename_groups(&mut self, file_name: &str) -> Result<(), std::io::Error> {
+        // only affects the labels of groups in the output, must be called after all computations
+        // that refer to groups by name
+        log::info!("loading group labels from {}", file_name);
+        let mut data = open_file(file_name)?;
+        let labels = io::parse_group_rename(&mut data)?;
+        let mut c = 0;
+        for group in self.groups.iter_mut() {
+            if let Some(label) = labels.get(group) {
+                *group = label.clone();
+                c += 1;
+            }
+        }
+        log::info!("renamed {} out of {} groups", c, self.groups.len());
+
+        let mut visited: HashSet<&str> = HashSet::new();
+        for group in self.groups.iter() {
+            if !visited.insert(group) {
+                warn_or_fail(
+                    self.graph_aux.strict,
+                    &format!(
+                        "label {} is used for more than one group after renaming",
+                        group
+                    ),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    // total length of the nodes traversed by each group, irrespective of the count type
+    fn group_bps(&self) -> Result<Vec<f64>, std::io::Error> {
+        if self.count == CountType::Edge {
+            let msg = "edges have no length, normalizing counts by length requires counting nodes, bps, or alleles";
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+        }
+        let mut res = vec![0.0; self.groups.len()];
+        let none = HashSet::new();
+        for i in 1..self.r.len() - 1 {
+            let w = self.item_weight(i, CountType::Bp, &none);
+            for g in &self.c[self.r[i]..self.r[i + 1]] {
+                res[*g as usize] += w;
+            }
+        }
+        Ok(res)
+    }
+
+    pub fn covered_size(&self, count: CountType) -> f64 {
+        // number of items (or bps) that are present in any group
+        let alleles = if count == CountType::Allele {
+            self.graph_aux.bubble_alleles()
+        } else {
+            Arc::default()
+        };
+        (1..self.r.len() - 1)
+            .filter(|&i| self.r[i] < self.r[i + 1])
+            .map(|i| self.item_weight(i, count, &alleles))
+            .sum()
+    }
+
+    fn group_index(&self, group: &str) -> Result<GroupSize, std::io::Error> {
+        match self.groups.iter().position(|x| x == group) {
+            Some(g) => Ok(g as GroupSize),
+            None => {
+                let msg = format!("unknown group {}", group);
+                log::error!("{}", &msg);
+                Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg))
+            }
+        }
+    }
+
+    pub fn group_size(&self, group: &str, count: CountType) -> Result<f64, std::io::Error> {
+        // number of items (or bps) that are present in the given group
+        let g = self.group_index(group)?;
+        let alleles = if count == CountType::Allele {
+            self.graph_aux.bubble_alleles()
+        } else {
+            Arc::default()
+        };
+
+        let mut res = 0.0;
+        let mut it = self.r.iter().tuple_windows().enumerate();
+        // ignore first entry
+        it.next();
+        for (i, (&start, &end)) in it {
+            if !self.c[start..end].contains(&g) {
+                continue;
+            }
+            match count {
+                CountType::Node | CountType::Edge => res += 1.0,
+                CountType::Allele => {
+                    if alleles.contains(&(i as ItemIdSize)) {
+                        res += 1.0
+                    }
+                }
+                CountType::Bp => {
+                    res += (self.graph_aux.node_len_ary[i] as usize
+                        - *self.uncovered_bps.get(&(i as ItemIdSize)).unwrap_or(&0))
+                        as f64
+                }
+            }
+        }
+        Ok(res)
+    }
+
     pub fn write_rcv<W: Write>(&self, out: &mut BufWriter<W>) -> Result<(), std::io::Error> {
         write!(out, "{}", self.r[0])?;
         for x in self.r[1..].iter() {
@@ -718,21 +2043,57 @@ impl AbacusByGroup {
         Ok(())
     }
 
+    fn write_group_name_rows<W: Write>(
+        &self,
+        skip: usize,
+        out: &mut BufWriter<W>,
+    ) -> Result<(), std::io::Error> {
+        // one row per field of the group names, aligned with the group columns of the table, i.e.,
+        // skipping the columns in between the first and the group columns
+        let fields: Vec<[String; 3]> = self.groups.iter().map(|g| pansn_fields(g)).collect();
+        for (k, name) in ["sample", "haplotype", "seqid"].iter().enumerate() {
+            write!(out, "{}{}", name, "\t".repeat(skip))?;
+            for f in fields.iter() {
+                write!(out, "\t{}", f[k])?;
+            }
+            writeln!(out, "")?;
+        }
+        Ok(())
+    }
+
     pub fn to_tsv<W: Write>(
         &self,
         total: bool,
+        with_length: bool,
+        normalize_by_length: bool,
+        head: Option<usize>,
+        split_group_columns: bool,
         out: &mut BufWriter<W>,
     ) -> Result<(), std::io::Error> {
-        // create mapping from numerical node ids to original node identifiers
-        let dummy = Vec::new();
-        let mut id2node: Vec<&Vec<u8>> = vec![&dummy; self.graph_aux.number_of_nodes() + 1];
-        for (node, id) in self.graph_aux.node2id.iter() {
-            id2node[id.0 as usize] = node;
-        }
+        // number of reported items, all if not specified otherwise
+        let head = head.unwrap_or(usize::MAX);
+        let bps = if normalize_by_length {
+            Some(self.group_bps()?)
+        } else {
+            None
+        };
+        let id2node = self.graph_aux.id2node();
+
+        let alleles = if self.count == CountType::Allele {
+            self.graph_aux.bubble_alleles()
+        } else {
+            Arc::default()
+        };
 
         match self.count {
-            CountType::Node | CountType::Bp => {
+            CountType::Node | CountType::Bp | CountType::Allele => {
+                if split_group_columns {
+                    self.write_group_name_rows(if with_length { 1 } else { 0 }, out)?;
+                }
                 write!(out, "node")?;
+                if with_length {
+                    write!(out, "\tlength")?;
+                }
                 if total {
                     write!(out, "\ttotal")?;
                 } else {
@@ -745,14 +2106,22 @@ impl AbacusByGroup {
                 let mut it = self.r.iter().tuple_windows().enumerate();
                 // ignore first entry
                 it.next();
-                for (i, (&start, &end)) in it {
+                for (i, (&start, &end)) in it
+                    .filter(|(i, _)| {
+                        self.count != CountType::Allele || alleles.contains(&(*i as ItemIdSize))
+                    })
+                    .take(head)
+                {
                     let bp = if self.count == CountType::Bp {
                         self.graph_aux.node_len_ary[i] as usize
                             - *self.uncovered_bps.get(&(i as ItemIdSize)).unwrap_or(&0)
                     } else {
                         1
                     };
-                    write!(out, "{}", std::str::from_utf8(id2node[i]).unwrap())?;
+                    write!(out, "{}", &id2node[i])?;
+                    if with_length {
+                        write!(out, "\t{}", self.graph_aux.node_len_ary[i])?;
+                    }
                     if total {
                         // we never need to look into the actual value in self.v, because we
                         // know it must be non-zero, which is sufficient
@@ -763,9 +2132,16 @@ impl AbacusByGroup {
                             if k == end || j < self.c[k] {
                                 write!(out, "\t0")?;
                             } else if j == self.c[k] {
-                                match &self.v {
-                                    None => write!(out, "\t{}", bp),
-                                    Some(v) => write!(out, "\t{}", v[k] as usize * bp),
+                                let x = match &self.v {
+                                    None => bp,
+                                    Some(v) => v[k] as usize * bp,
+                                };
+                                match (&self.path_fractions, &bps) {
+                                    (Some(f), _) => write!(out, "\t{}", f[k] * bp as f64),
+                                    (None, None) => write!(out, "\t{}", x),
+                                    (None, Some(bps)) => {
+                                        write!(out, "\t{}", density(x as f64, bps[j as usize]))
+                                    }
                                 }?;
                                 k += 1;
                             }
@@ -775,6 +2151,12 @@ impl AbacusByGroup {
                 }
             }
             CountType::Edge => {
+                if with_length {
+                    warn_or_fail(
+                        self.graph_aux.strict,
+                        "edges have no length, omitting length column",
+                    )?;
+                }
                 if let Some(edge2id) = &self.graph_aux.edge2id {
                     let dummy_edge = Edge(
                         ItemId(0),
@@ -788,6 +2170,9 @@ impl AbacusByGroup {
                         id2edge[id.0 as usize] = edge;
                     }
 
+                    if split_group_columns {
+                        self.write_group_name_rows(0, out)?;
+                    }
                     write!(out, "edge")?;
                     if total {
                         write!(out, "\ttotal")?;
@@ -801,18 +2186,11 @@ impl AbacusByGroup {
                     let mut it = self.r.iter().tuple_windows().enumerate();
                     // ignore first entry
                     it.next();
-                    for (i, (&start, &end)) in it {
+                    for (i, (&start, &end)) in it.take(head) {
                         let edge = id2edge[i];
                         let start = start as usize;
                         let end = end as usize;
-                        write!(
-                            out,
-                            "{}{}{}{}",
-                            edge.1,
-                            std::str::from_utf8(id2node[edge.0 .0 as usize]).unwrap(),
-                            edge.3,
-                            std::str::from_utf8(id2node[edge.2 .0 as usize]).unwrap(),
-                        )?;
+                        write!(out, "{}", self.graph_aux.edge_name(&id2node, edge))?;
                         if total {
                             // we never need to look into the actual value in self.v, because we
                             // know it must be non-zero, which is sufficient
@@ -823,9 +2201,10 @@ impl AbacusByGroup {
                                 if k == end || j < self.c[k] {
                                     write!(out, "\t0")?;
                                 } else if j == self.c[k] {
-                                    match &self.v {
-                                        None => write!(out, "\t1"),
-                                        Some(v) => write!(out, "\t{}", v[j as usize]),
+                                    match (&self.path_fractions, &self.v) {
+                                        (Some(f), _) => write!(out, "\t{}", f[k]),
+                                        (None, None) => write!(out, "\t1"),
+                                        (None, Some(v)) => write!(out, "\t{}", v[k]),
                                     }?;
                                     k += 1;
                                 }
@@ -890,3 +2269,339 @@ fn quantify_uncovered_bps(
     }
     res
 }
+
+fn pansn_fields(group: &str) -> [String; 3] {
+    // group names follow the PanSN naming scheme of the paths they are derived from, i.e.,
+    // sample#haplotype#seqid, where trailing fields may be missing
+    let mut it = group.splitn(3, '#').map(|x| x.to_string());
+    [
+        it.next().unwrap_or_default(),
+        it.next().unwrap_or_default(),
+        it.next().unwrap_or_default(),
+    ]
+}
+
+fn density(count: f64, bps: f64) -> f64 {
+    // groups that traverse no sequence at all have no meaningful density
+    if bps > 0.0 {
+        count / bps
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hist::Hist;
+
+    fn index_graph(gfa: &[u8], count: CountType) -> GraphAuxilliary {
+        let mut graph_aux = GraphAuxilliary::from_gfa(
+            &mut std::io::BufReader::new(gfa),
+            &IndexOptions {
+                index_edges: count.requires_edge_index(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        if count.requires_edge_index() {
+            graph_aux.add_path_edges();
+        }
+        graph_aux
+    }
+
+    fn abacus_aux(
+        graph_aux: &GraphAuxilliary,
+        count: CountType,
+        groupby_sample: bool,
+        include: &[&str],
+    ) -> AbacusAuxilliary {
+        AbacusAuxilliary {
+            count: count,
+            groups: AbacusAuxilliary::load_groups("", false, groupby_sample, false, &graph_aux)
+                .unwrap(),
+            include_coords: if include.is_empty() {
+                None
+            } else {
+                Some(include.iter().map(|x| PathSegment::from_str(x)).collect())
+            },
+            exclude_coords: None,
+            order: None,
+            order_weights: None,
+            depth_range: None,
+            reference: None,
+            reference_coords: None,
+            relative_to_reference: false,
+            multiplicity: false,
+            shards: None,
+        }
+    }
+
+    fn abacus_total(
+        gfa: &[u8],
+        count: CountType,
+        groupby_sample: bool,
+        include: &[&str],
+    ) -> AbacusByTotal {
+        let graph_aux = index_graph(gfa, count);
+        let abacus_aux = abacus_aux(&graph_aux, count, groupby_sample, include);
+        AbacusByTotal::from_gfa(&mut std::io::BufReader::new(gfa), abacus_aux, graph_aux).unwrap()
+    }
+
+    const SEGMENTS: &[u8] = b"S\t1\tAAA\nS\t2\tCC\nS\t3\tG\nS\t4\tTT\nL\t1\t+\t2\t+\t0M\nL\t2\t+\t3\t+\t0M\nL\t1\t+\t3\t+\t0M\nL\t3\t+\t4\t-\t0M\n";
+
+    #[test]
+    fn test_walks_equal_paths() {
+        let paths = [
+            SEGMENTS,
+            b"P\tA#1#c\t1+,2+,3+,4-\t*\nP\tB#1#c\t1+,3+,4-\t*\nP\tC#2#c\t4+,3-,1-\t*\n",
+        ]
+        .concat();
+        let walks = [
+            SEGMENTS,
+            b"W\tA\t1\tc\t0\t8\t>1>2>3<4\nW\tB\t1\tc\t0\t6\t>1>3<4\nW\tC\t2\tc\t0\t6\t>4<3<1\n",
+        ]
+        .concat();
+        for count in [CountType::Node, CountType::Bp, CountType::Edge] {
+            let p = abacus_total(&paths, count, false, &[]);
+            let w = abacus_total(&walks, count, false, &[]);
+            assert_eq!(p.countable, w.countable, "{} counts differ", count);
+            assert_eq!(
+                Hist::from_abacus(&p).coverage,
+                Hist::from_abacus(&w).coverage,
+                "{} histograms differ",
+                count
+            );
+        }
+    }
+
+    #[test]
+    fn test_group_counts_shared_node_once() {
+        // node 1 is shared by both paths of sample A, but is covered by groups A and B only
+        let gfa = [
+            SEGMENTS,
+            b"P\tA#1#c\t1+,2+\t*\nP\tA#2#c\t1+,3+\t*\nP\tB#1#c\t1+\t*\n",
+        ]
+        .concat();
+        let abacus = abacus_total(&gfa, CountType::Bp, true, &[]);
+        assert_eq!(abacus.countable[1..4], [2, 1, 1]);
+        assert_eq!(Hist::from_abacus(&abacus).coverage, vec![2, 3, 3]);
+    }
+
+    #[test]
+    fn test_orientation_counts_node_once() {
+        // nodes 1 and 2 are traversed in both orientations by sample B, even within one path
+        let gfa = [
+            SEGMENTS,
+            b"L\t2\t+\t2\t-\t0M\nP\tA#1#c\t1+,2+\t*\nP\tB#1#c\t1+,2+,2-,1-\t*\nP\tB#2#c\t2-,1-\t*\n",
+        ]
+        .concat();
+        for count in [CountType::Node, CountType::Bp] {
+            let abacus = abacus_total(&gfa, count, true, &[]);
+            assert_eq!(abacus.countable[1..5], [2, 2, 0, 0]);
+        }
+        let abacus = abacus_total(&gfa, CountType::Bp, true, &[]);
+        assert_eq!(Hist::from_abacus(&abacus).coverage, vec![3, 0, 5]);
+    }
+
+    #[test]
+    fn test_edge_without_link() {
+        // edge >2>4 is traversed in both orientations, but not represented by an L-line
+        let gfa = [
+            SEGMENTS,
+            b"P\tA#1#c\t1+,2+,4+\t*\nW\tB\t1\tc\t0\t7\t>1>2>4\nW\tC\t1\tc\t0\t4\t<4<2\n",
+        ]
+        .concat();
+        let abacus = abacus_total(&gfa, CountType::Edge, false, &[]);
+        assert_eq!(abacus.graph_aux.edge_count, 5);
+        let e = abacus.graph_aux.edge_id(">2>4").unwrap();
+        assert_eq!(abacus.countable[e.0 as usize], 3);
+        assert_eq!(Hist::from_abacus(&abacus).coverage, vec![3, 0, 1, 1]);
+    }
+
+    #[test]
+    fn test_subset_boundary_edges() {
+        // nodes 1, 2, 3, and 4 span [0, 3), [3, 5), [5, 6), and [6, 8) of the path
+        let gfa = [SEGMENTS, b"P\tA#1#c\t1+,2+,3+,4-\t*\n"].concat();
+        let covered = |include: &[&str]| -> Vec<CountSize> {
+            let abacus = abacus_total(&gfa, CountType::Edge, false, include);
+            [">1>2", ">2>3", ">3<4"]
+                .iter()
+                .map(|e| abacus.countable[abacus.graph_aux.edge_id(e).unwrap().0 as usize])
+                .collect()
+        };
+        // edges straddling the end or the start of an interval are not counted
+        assert_eq!(covered(&["A#1#c:0-5"]), vec![1, 0, 0]);
+        assert_eq!(covered(&["A#1#c:4-8"]), vec![0, 0, 1]);
+        // adjacent intervals are merged, so that edges spanning their junction are counted
+        assert_eq!(covered(&["A#1#c:0-5", "A#1#c:5-8"]), vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn test_node_bp_presence_agree() {
+        // sample A traverses node 1 three times and node 3 twice, in either orientation
+        let gfa = [
+            SEGMENTS,
+            b"L\t2\t+\t1\t+\t0M\nP\tA#1#c\t1+,2+,1+,3+,4-\t*\nP\tA#2#c\t1+,3+\t*\nP\tB#1#c\t4+,3-,1-\t*\n",
+        ]
+        .concat();
+        let node = abacus_total(&gfa, CountType::Node, true, &[]);
+        let bp = abacus_total(&gfa, CountType::Bp, true, &[]);
+        assert_eq!(node.countable, bp.countable);
+        assert_eq!(node.countable[1..5], [2, 1, 2, 2]);
+
+        // each bp bin comprises the lengths of the nodes in the corresponding node bin
+        let mut expected = vec![0; 3];
+        for (i, c) in node.countable.iter().enumerate().skip(1) {
+            expected[*c as usize] += node.graph_aux.node_len(&ItemId(i as ItemIdSize)) as usize;
+        }
+        assert_eq!(Hist::from_abacus(&bp).coverage, expected);
+    }
+
+    #[test]
+    fn test_mixed_line_endings() {
+        let unix = [
+            SEGMENTS,
+            b"P\tA#1#c\t1+,2+,3+,4-\t*\nW\tB\t1\tc\t0\t6\t>1>3<4\n",
+        ]
+        .concat();
+        // every other line ends with "\r\n", including the S-lines, P-lines, and W-lines
+        let mixed: Vec<u8> = unix
+            .split(|&x| x == b'\n')
+            .filter(|x| !x.is_empty())
+            .enumerate()
+            .flat_map(|(i, x)| [x, if i % 2 == 0 { &b"\r\n"[..] } else { &b"\n"[..] }].concat())
+            .collect();
+        for count in [CountType::Bp, CountType::Edge] {
+            let u = abacus_total(&unix, count, false, &[]);
+            let m = abacus_total(&mixed, count, false, &[]);
+            assert_eq!(u.graph_aux.node_len_ary, m.graph_aux.node_len_ary);
+            assert_eq!(u.graph_aux.edge_count, m.graph_aux.edge_count);
+            assert_eq!(u.countable, m.countable, "{} counts differ", count);
+        }
+    }
+
+    #[test]
+    fn test_item_subsample_converges() {
+        let gfa = io::synthetic_gfa(5000, 20, 42);
+        let graph_aux = index_graph(&gfa, CountType::Node);
+        let abacus_aux = abacus_aux(&graph_aux, CountType::Node, false, &[]);
+        let abacus = AbacusByGroup::from_gfa(
+            &mut std::io::BufReader::new(&gfa[..]),
+            abacus_aux,
+            graph_aux,
+            false,
+            false,
+        )
+        .unwrap();
+        for q in [0.0, 0.5, 1.0] {
+            let (c, q) = (Threshold::Absolute(1), Threshold::Relative(q));
+            let full = abacus.calc_growth(&c, &q, CountType::Node, None, Direction::Grow, false);
+            // largest deviation of the subsampled curve relative to the full one
+            let error = |fraction: f64| -> f64 {
+                let items = abacus.sample_items(fraction, Some(7)).unwrap();
+                abacus
+                    .calc_growth(
+                        &c,
+                        &q,
+                        CountType::Node,
+                        Some(&items),
+                        Direction::Grow,
+                        false,
+                    )
+                    .iter()
+                    .zip(&full)
+                    .map(|(s, f)| (s - f).abs() / f.max(1.0))
+                    .fold(0.0, f64::max)
+            };
+            let errors: Vec<f64> = [0.1, 0.5, 1.0].iter().map(|&f| error(f)).collect();
+            // the larger the sample, the closer its curve to the full one, up to identity
+            assert!(errors[0] < 0.25, "{:?}", errors);
+            assert!(errors[1] < 0.05 && errors[1] <= errors[0], "{:?}", errors);
+            assert!(errors[2] < 1e-9, "{:?}", errors);
+        }
+    }
+
+    #[test]
+    fn test_stepwise_thresholds() {
+        // node 2 is present in the first two groups only, node 3 in the last two
+        let gfa = [
+            SEGMENTS,
+            b"P\tA#1#c\t1+,2+\t*\nP\tB#1#c\t1+,2+,3+\t*\nP\tC#1#c\t1+,3+,4-\t*\n",
+        ]
+        .concat();
+        let graph_aux = index_graph(&gfa, CountType::Node);
+        let abacus_aux = abacus_aux(&graph_aux, CountType::Node, false, &[]);
+        let abacus = AbacusByGroup::from_gfa(
+            &mut std::io::BufReader::new(&gfa[..]),
+            abacus_aux,
+            graph_aux,
+            false,
+            false,
+        )
+        .unwrap();
+        let (c, q) = (Threshold::Absolute(1), Threshold::Relative(1.0));
+        let growth =
+            |stepwise| abacus.calc_growth(&c, &q, CountType::Node, None, Direction::Grow, stepwise);
+        // by default, quorum refers to the groups up to the last one containing the node, so
+        // that node 2 remains in the core after adding the third group
+        assert_eq!(growth(false), [2.0, 2.0, 2.0]);
+        assert_eq!(growth(true), [2.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn test_growth_in_order_with_repeats() {
+        let gfa = [
+            SEGMENTS,
+            b"P\tA#1#c\t1+,2+\t*\nP\tB#1#c\t1+,2+,3+\t*\nP\tC#1#c\t1+,3+,4-\t*\n",
+        ]
+        .concat();
+        let graph_aux = index_graph(&gfa, CountType::Node);
+        let abacus_aux = abacus_aux(&graph_aux, CountType::Node, false, &[]);
+        let abacus = AbacusByGroup::from_gfa(
+            &mut std::io::BufReader::new(&gfa[..]),
+            abacus_aux,
+            graph_aux,
+            false,
+            false,
+        )
+        .unwrap();
+        let c = Threshold::Absolute(1);
+        // without repeats, the order of the abacus gives the ordered growth
+        for q in [0.0, 0.5, 1.0] {
+            let q = Threshold::Relative(q);
+            for stepwise in [false, true] {
+                assert_eq!(
+                    abacus.calc_growth_in_order(&[0, 1, 2], &c, &q, CountType::Node, stepwise),
+                    abacus.calc_growth(&c, &q, CountType::Node, None, Direction::Grow, stepwise)
+                );
+            }
+        }
+        // a group drawn twice covers its nodes twice
+        let (q0, q1) = (Threshold::Relative(0.0), Threshold::Relative(1.0));
+        let growth = |order: &[GroupSize], q| {
+            abacus.calc_growth_in_order(order, &c, q, CountType::Node, true)
+        };
+        assert_eq!(growth(&[1, 1, 0], &q0), [3.0, 3.0, 3.0]);
+        assert_eq!(growth(&[1, 1, 0], &q1), [3.0, 3.0, 2.0]);
+        assert_eq!(growth(&[0, 0, 2], &q0), [2.0, 2.0, 4.0]);
+        assert_eq!(
+            abacus.calc_growth_in_order(
+                &[1, 1, 2],
+                &Threshold::Absolute(2),
+                &q0,
+                CountType::Bp,
+                true
+            ),
+            [0.0, 6.0, 6.0]
+        );
+
+        // samples are drawn with replacement and reproducible given a seed
+        let orders = abacus.bootstrap_orders(20, Some(3));
+        assert_eq!(orders, abacus.bootstrap_orders(20, Some(3)));
+        assert!(orders
+            .iter()
+            .all(|o| o.len() == 3 && o.iter().all(|g| *g < 3)));
+        assert!(orders.iter().any(|o| o.iter().unique().count() < 3));
+    }
+}
@@ -1,10 +1,12 @@
 /* standard crate */
+use std::collections::HashMap;
 use std::fs;
 use std::io::{BufWriter, Write};
 use std::str::FromStr;
+use std::time::Instant;
 
 /* external crate */
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use rayon::prelude::*;
 use strum::VariantNames;
 
@@ -12,6 +14,8 @@ use strum::VariantNames;
 use crate::abacus::*;
 use crate::graph::*;
 use crate::hist::*;
+use crate::html;
+use crate::io;
 use crate::util::*;
 
 pub enum RequireThreshold {
@@ -42,6 +46,43 @@ macro_rules! clap_enum_variants {
 struct Command {
     #[clap(subcommand)]
     cmd: Params,
+
+    #[clap(
+        long,
+        global = true,
+        help = "Treat warnings as errors, e.g., about nodes in paths/walks that are not defined by any S-line, which are otherwise skipped"
+    )]
+    strict: bool,
+
+    #[clap(
+        long,
+        global = true,
+        help = "Write a structured record (JSON) of the run, comprising input sizes, counts, thresholds, timings, and peak memory, to the given file"
+    )]
+    stats_file: Option<String>,
+
+    #[clap(
+        long,
+        global = true,
+        help = "Write key counts of the run (nodes, edges, paths, groups, countables) and timings as metrics in Prometheus text exposition format to the given file"
+    )]
+    metrics_file: Option<String>,
+
+    #[clap(
+        long,
+        global = true,
+        help = "Print the resolved configuration (count type, number of groups, coverage/quorum thresholds after broadcasting, input files) as one-line JSON to stderr before counting"
+    )]
+    print_config: bool,
+
+    #[clap(
+        long,
+        global = true,
+        help = "Size (in bytes) of the read buffer of input files; larger buffers may improve throughput on high-latency file systems",
+        default_value = "8192",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    buffer_size: usize,
 }
 
 #[derive(Subcommand, Debug)]
@@ -51,9 +92,6 @@ pub enum Params {
         about = "Run in default mode, i.e., run hist and growth successively and output the results of the latter"
     )]
     Histgrowth {
-        #[clap(index = 1, help = "graph in GFA1 format", required = true)]
-        gfa_file: String,
-
         #[clap(short, long,
         help = "Graph quantity to be counted",
         default_value = "node",
@@ -62,75 +100,81 @@ pub enum Params {
     )]
         count: CountType,
 
-        #[clap(
-            name = "subset",
-            short,
-            long,
-            help = "Produce counts by subsetting the graph to a given list of paths (1-column list) or path coordinates (3- or 12-column BED file)",
-            default_value = ""
-        )]
-        positive_list: String,
+        #[clap(flatten)]
+        abacus_args: AbacusArgs,
+
+        #[clap(flatten)]
+        growth_args: GrowthArgs,
 
         #[clap(
-            name = "exclude",
-            short,
+            short = 'o',
             long,
-            help = "Exclude bp/node/edge in growth count that intersect with paths (1-column list) or path coordinates (3- or 12-column BED-file) provided by the given file",
-            default_value = ""
+            help = "Output format: \"table\" reports the growth as tab-separated values, \"html\" reports a self-contained HTML page with interactive histogram and growth plots as well as graph statistics",
+            default_value = "table",
+            ignore_case = true,
+            value_parser = clap_enum_variants!(OutputFormat),
         )]
-        negative_list: String,
+        output_format: OutputFormat,
 
         #[clap(
-            short,
             long,
-            help = "Merge counts from paths by path-group mapping from given tab-separated two-column file",
-            default_value = ""
+            help = "Compute coverage with a simple per-group set and counter map instead of the optimized item table, to cross-check results on small graphs; slow, for validation only, and cannot be combined with subset/exclude/depth/reference settings"
         )]
-        groupby: String,
+        naive_count: bool,
 
         #[clap(
-            short = 'H',
             long,
-            help = "Merge counts from paths belonging to same haplotype"
+            help = "Exclude private countables, i.e., those covered by exactly one group, by zeroing the coverage-1 bin of the histogram (including the reported one) before computing growth, e.g., for shell genome growth. Has no effect if the coverage threshold is 2 or more and on the core curve; with a relative quorum q > 0, private countables only contribute to the first floor(1/q) steps, so only these steps change"
         )]
-        groupby_haplotype: bool,
-
+        exclude_private: bool,
         #[clap(
-            short = 'S',
             long,
-            help = "Merge counts from paths belonging to same sample"
+            help = "State file accumulating node coverage over several runs on graphs that share node IDs: if the file exists, the coverage recorded in it is added to that of this run (whose groups must not be recorded in it already), then the updated coverage is written back to the file; requires counting nodes",
+            default_value = ""
         )]
-        groupby_sample: bool,
-
+        accumulate: String,
         #[clap(
-            short,
             long,
-            help = "List of quorum fractions of the form <level1>,<level2>,... Number of values must be one or match that of coverage setting",
-            default_value = "0"
+            help = "Report growth as fraction of a total (with the number of decimal places given by the precision setting): \"paths\" divides by the number of countables traversed by any path (after applying subset/exclude settings), so that the last value of the union curve is 1; \"graph\" divides by the number of all nodes, edges, bps, or alleles of the graph, irrespective of whether paths traverse them. Relative coverage and quorum thresholds always refer to the number of groups and are not affected",
+            ignore_case = true,
+            value_parser = clap_enum_variants!(Denominator),
         )]
-        quorum: String,
-
+        denominator: Option<Denominator>,
         #[clap(
-            short = 'l',
             long,
-            help = "List of absolute coverage thresholds of the form <level1>,<level2>,... Number of values must be one or match that of quorum setting",
-            default_value = "1"
+            help = "Additionally write the coverage histogram, as reported by the hist command, to the given file",
+            default_value = ""
         )]
-        coverage: String,
+        save_hist: String,
+    },
+    #[clap(
+        about = "Run hist and growth successively and report their results, along with graph statistics, as a self-contained HTML page with interactive histogram and growth plots"
+    )]
+    Report {
+        #[clap(short, long,
+        help = "Graph quantity to be counted",
+        default_value = "node",
+        ignore_case = true,
+        value_parser = clap_enum_variants!(CountType),
+    )]
+        count: CountType,
+
+        #[clap(flatten)]
+        abacus_args: AbacusArgs,
+
+        #[clap(flatten)]
+        growth_args: GrowthArgs,
 
         #[clap(
-            short,
+            short = 'o',
             long,
-            help = "Run in parallel on N threads",
-            default_value = "1"
+            help = "Write the report to the given file instead of standard output",
+            default_value = ""
         )]
-        threads: usize,
+        output: String,
     },
     #[clap(alias = "h", about = "Calculate coverage histogram from GFA file")]
     Hist {
-        #[clap(index = 1, help = "graph in GFA1 format", required = true)]
-        gfa_file: String,
-
         #[clap(short, long,
         help = "Graph quantity to be counted",
         default_value = "node",
@@ -139,53 +183,47 @@ pub enum Params {
     )]
         count: CountType,
 
+        #[clap(flatten)]
+        abacus_args: AbacusArgs,
+
         #[clap(
-            name = "subset",
-            short,
             long,
-            help = "Produce counts by subsetting the graph to a given list of paths (1-column list) or path coordinates (3- or 12-column BED file)",
-            default_value = ""
+            help = "Additionally report, for each coverage, the number of countables with at least and with at most that coverage"
         )]
-        positive_list: String,
+        all_cumulatives: bool,
 
         #[clap(
-            name = "exclude",
-            short,
             long,
-            help = "Exclude bp/node/edge in growth count that intersect with paths (1-column list) or path coordinates (3- or 12-column BED-file) provided by the given file; all intersecting bp/node/edge will be exluded also in other paths not part of the given list",
-            default_value = ""
+            help = "Report only coverage bins from the given one on; e.g., 1 drops bin 0 of countables covered by no group",
+            default_value = "0"
         )]
-        negative_list: String,
+        min_coverage_bin: usize,
 
         #[clap(
-            short,
             long,
-            help = "Merge counts from paths by path-group mapping from given tab-separated two-column file",
+            help = "Additionally write, for each coverage level c, the IDs of the nodes (or edges) of that coverage to file <prefix>.<c>.txt, one per line",
             default_value = ""
         )]
-        groupby: String,
+        list_items: String,
 
         #[clap(
-            short = 'H',
             long,
-            help = "Merge counts from paths belonging to same haplotype"
+            help = "Compute coverage with a simple per-group set and counter map instead of the optimized item table, to cross-check results on small graphs; slow, for validation only, and cannot be combined with subset/exclude/depth/reference settings"
         )]
-        groupby_haplotype: bool,
+        naive_count: bool,
 
         #[clap(
-            short = 'S',
             long,
-            help = "Merge counts from paths belonging to same sample"
+            help = "State file accumulating node coverage over several runs on graphs that share node IDs: if the file exists, the coverage recorded in it is added to that of this run (whose groups must not be recorded in it already), then the updated coverage is written back to the file; requires counting nodes",
+            default_value = ""
         )]
-        groupby_sample: bool,
-
+        accumulate: String,
         #[clap(
-            short,
             long,
-            help = "Run in parallel on N threads",
-            default_value = "1"
+            help = "Count every traversal of a countable rather than its presence: by default, a countable is covered once by each group whose paths traverse it at least once, irrespective of how often they do, which applies to nodes, edges, and bp alike. With this option, coverage is the total number of traversals by the paths of all groups and may thus exceed the number of groups",
+            conflicts_with_all = ["naive_count", "accumulate"]
         )]
-        threads: usize,
+        multiplicity: bool,
     },
 
     #[clap(alias = "g", about = "Construct growth table from coverage histogram")]
@@ -197,22 +235,31 @@ pub enum Params {
         )]
         hist_file: String,
 
+        #[clap(flatten)]
+        growth_args: GrowthArgs,
+
         #[clap(
-            short,
+            short = 'c',
             long,
-            help = "List of quorum fractions of the form <level1>,<level2>,... Number of values must be one or match that of coverage setting",
-            default_value = "0"
+            help = "Count type the histogram is expected to report; if the histogram header specifies a different count type, an error is raised. If neither is given, \"node\" is assumed",
+            ignore_case = true,
+            value_parser = clap_enum_variants!(CountType),
         )]
-        quorum: String,
+        count: Option<CountType>,
 
         #[clap(
-            short = 'l',
             long,
-            help = "List of absolute coverage thresholds of the form <level1>,<level2>,... Number of values must be one or match that of quorum setting",
-            default_value = "1"
+            help = "Instead of the growth table, report the parameters of the given model fitted to the growth data by least squares: \"tettelin-core\" fits n(N) = k * exp(-N / tau) + omega to the core growth curve, where omega is the extrapolated core size and tau the decay constant",
+            ignore_case = true,
+            value_parser = clap_enum_variants!(FitModel),
         )]
-        coverage: String,
+        fit_model: Option<FitModel>,
 
+        #[clap(
+            long,
+            help = "Exclude private countables, i.e., those covered by exactly one group, by zeroing the coverage-1 bin of the histogram (including the reported one) before computing growth, e.g., for shell genome growth. Has no effect if the coverage threshold is 2 or more and on the core curve; with a relative quorum q > 0, private countables only contribute to the first floor(1/q) steps, so only these steps change"
+        )]
+        exclude_private: bool,
         #[clap(
             short,
             long,
@@ -227,9 +274,6 @@ pub enum Params {
         about = "Compute growth table for order specified in grouping file (or, if non specified, the order of paths in the GFA file)"
     )]
     OrderedHistgrowth {
-        #[clap(index = 1, help = "graph in GFA1 format", required = true)]
-        gfa_file: String,
-
         #[clap(short, long,
         help = "Graph quantity to be counted",
         default_value = "node",
@@ -242,81 +286,108 @@ pub enum Params {
             name = "order",
             short,
             long,
-            help = "The ordered histogram will be produced according to order of paths/groups in the supplied file (1-column list). If this option is not used, the order is determined by the rank of paths/groups in the subset list, and if that option is not used, the order is determined by the rank of paths/groups in the GFA file.",
+            help = "The ordered histogram will be produced according to order of paths/groups in the supplied file (1-column list). Paths/groups can be given a weight (e.g., a phylogenetic branch length) in an optional second column, in which case rows without weight count as 1 and the growth is additionally reported along the cumulative weight of groups. If this option is not used, the order is determined by the rank of paths/groups in the subset list, and if that option is not used, the order is determined by the rank of paths/groups in the GFA file.",
             default_value = ""
         )]
         order: String,
 
         #[clap(
-            name = "subset",
-            short,
             long,
-            help = "Produce counts by subsetting the graph to a given list of paths (1-column list) or path coordinates (3- or 12-column BED file). If the \"order\" option is not used, the subset list will also indicate the order of paths/groups in the histogram.",
+            help = "The ordered histogram will be produced according to the query sequences of the supplied alignment file (PAF or GAF), ranked by descending mapping quality; ties are resolved by the order of first occurrence in the file. Query names that do not match a path are interpreted as group identifiers. Cannot be combined with the \"order\" option.",
             default_value = ""
         )]
-        positive_list: String,
+        order_paf: String,
+
+        #[clap(flatten)]
+        abacus_args: AbacusArgs,
+
+        #[clap(flatten)]
+        growth_args: GrowthArgs,
 
         #[clap(
-            name = "exclude",
-            short,
             long,
-            help = "Exclude bp/node/edge in growth count that intersect with paths (1-column list) or path coordinates (3- or 12-column BED-file) provided by the given file",
-            default_value = ""
+            help = "Subtract the number of countables of the given group from each value of the growth curve (clamped at zero), i.e., report growth beyond that group. For quorum > 0, values are differences in size between the countables satisfying coverage/quorum and those of the baseline group, not set differences"
         )]
-        negative_list: String,
+        baseline: Option<String>,
 
         #[clap(
-            short,
             long,
-            help = "Merge counts from paths by path-group mapping from given tab-separated two-column file",
-            default_value = ""
+            help = "Report ordered growth for several count types at once, given as list <type1>,<type2>,...; currently only node and bp are supported and require count type node",
+            value_delimiter = ',',
+            ignore_case = true,
+            value_parser = clap_enum_variants!(CountType),
         )]
-        groupby: String,
+        dual_count: Vec<CountType>,
 
         #[clap(
-            short = 'H',
             long,
-            help = "Merge counts from paths belonging to same haplotype"
+            help = "Tab-separated file mapping group names to the labels used in the output (two columns: old name, new name); groups not listed keep their names. Other options still refer to the original group names",
+            default_value = ""
         )]
-        groupby_haplotype: bool,
+        group_rename: String,
 
         #[clap(
-            short = 'S',
             long,
-            help = "Merge counts from paths belonging to same sample"
+            help = "Report groups in descending order of their marginal contribution, i.e., the increase of the (first) growth curve when adding the group (or, for decay, its decrease when removing the group); values are not recomputed and still refer to the position of the group in the order"
         )]
-        groupby_sample: bool,
-
+        sort_groups_by_contribution: bool,
         #[clap(
-            short,
             long,
-            help = "List of quorum fractions of the form <level1>,<level2>,... Number of values must be one or match that of coverage setting",
-            default_value = "0"
+            help = "Additionally write to the given file a table with the number of nodes (or edges) and bps that each group newly adds in the given order (or, for decay, that are lost when removing the group), along with their cumulative sums; this exposes the steps of the growth curve",
+            default_value = ""
         )]
-        quorum: String,
-
+        audit_contributions: String,
         #[clap(
-            short = 'l',
             long,
-            help = "List of absolute coverage thresholds of the form <level1>,<level2>,... Number of values must be one or match that of quorum setting",
-            default_value = "1"
+            help = "Report growth as fraction of a total (with the number of decimal places given by the precision setting): \"paths\" divides by the number of countables traversed by any path (after applying subset/exclude settings), so that the last value of the union curve is 1; \"graph\" divides by the number of all nodes, edges, bps, or alleles of the graph, irrespective of whether paths traverse them. Relative coverage and quorum thresholds always refer to the number of groups and are not affected",
+            ignore_case = true,
+            value_parser = clap_enum_variants!(Denominator),
         )]
-        coverage: String,
-
+        denominator: Option<Denominator>,
         #[clap(
-            short,
             long,
-            help = "Run in parallel on N threads",
-            default_value = "1"
+            help = "Approximate the growth curves by counting only a random sample of the given fraction of countables (seeded by the seed setting) and scaling up the result; speeds up computing growth for many coverage/quorum pairs on large graphs at the cost of sampling error, which grows as the fraction or the number of countables present in few groups shrinks"
         )]
-        threads: usize,
+        item_subsample: Option<f64>,
+        #[clap(
+            long,
+            help = "Direction of the curve: \"grow\" adds groups one at a time in the given order; \"decay\" starts from the full set and removes groups one at a time in the given order, i.e., the row of a group reports the countables of it and all groups that follow it. Efficiency, marginal contributions, cumulative weights, and the contribution audit refer to the removal of the group accordingly. With --stepwise-thresholds, coverage and quorum thresholds refer to the groups present at each step in either direction, so that decay with quorum 1 tracks the core of the remaining groups",
+            default_value = "grow",
+            ignore_case = true,
+            value_parser = clap_enum_variants!(Direction),
+        )]
+        direction: Direction,
+        #[clap(
+            long,
+            help = "Evaluate coverage and quorum thresholds w.r.t. the groups added so far, i.e., at step m, count countables that are present in at least max(coverage, ceil(quorum*m)) of the first m groups, as in (unordered) histgrowth and --soft-core. By default, coverage refers to all groups and quorum to the groups up to the last one that contains the countable"
+        )]
+        stepwise_thresholds: bool,
+        #[clap(
+            long,
+            help = "Instead of following a fixed order, draw the given number of bootstrap samples of the groups, each comprising as many groups as there are, drawn with replacement (seeded by the seed setting), and report for each step the mean and variance of the growth over all samples. A group drawn repeatedly contributes repeatedly to the coverage of its countables",
+            conflicts_with_all = [
+                "order",
+                "order_paf",
+                "baseline",
+                "dual_count",
+                "group_rename",
+                "sort_groups_by_contribution",
+                "audit_contributions",
+                "denominator",
+                "item_subsample",
+                "direction",
+                "long",
+                "sql_friendly",
+                "efficiency",
+            ]
+        )]
+        bootstrap: Option<usize>,
     },
 
-    #[clap(about = "Compute coverage table for count items")]
+    #[clap(
+        about = "Compute coverage table for count items, i.e., the number of times each group traverses each item (times its length when counting bp)"
+    )]
     Table {
-        #[clap(index = 1, help = "graph in GFA1 format", required = true)]
-        gfa_file: String,
-
         #[clap(short, long,
             help = "Graph quantity to be counted",
             default_value = "node",
@@ -325,236 +396,1499 @@ pub enum Params {
         )]
         count: CountType,
 
+        #[clap(
+            long,
+            help = "Report tables for several count types at once, given as list <type1>,<type2>,...; replaces the count setting. Tables are written one after another, each preceded by a comment line naming its count type. The GFA is scanned only once for node, bp, and allele counts (for bp counts, only if no subset/exclude coordinates are given), and once more for each other count type",
+            value_delimiter = ',',
+            ignore_case = true,
+            value_parser = clap_enum_variants!(CountType),
+        )]
+        count_types: Vec<CountType>,
+
         #[clap(
             name = "total",
             short = 'a',
             long,
-            help = "Summarize by totaling presence/absence over all groups"
+            help = "Summarize by totaling presence/absence over all groups; saves the memory of storing traversal counts, which takes twice as much as the table of group IDs itself"
         )]
         total: bool,
 
         #[clap(
-            name = "subset",
-            short,
             long,
-            help = "Produce counts by subsetting the graph to a given list of paths (1-column list) or path coordinates (3- or 12-column BED file)",
-            default_value = ""
+            help = "Report the length of each node in an additional column (ignored when counting edges)"
         )]
-        positive_list: String,
+        with_length: bool,
+
+        #[clap(flatten)]
+        abacus_args: AbacusArgs,
 
         #[clap(
-            name = "exclude",
-            short,
             long,
-            help = "Exclude bp/node/edge in growth count that intersect with paths (1-column list) or path coordinates (3- or 12-column BED-file) provided by the given file",
-            default_value = ""
+            help = "Only report the first N items, e.g., to inspect the output format"
         )]
-        negative_list: String,
+        head: Option<usize>,
 
         #[clap(
-            short,
             long,
-            help = "Merge counts from paths by path-group mapping from given tab-separated two-column file",
+            help = "Tab-separated file mapping group names to the labels used in the output (two columns: old name, new name); groups not listed keep their names. Other options still refer to the original group names",
             default_value = ""
         )]
-        groupby: String,
+        group_rename: String,
+
+        #[clap(
+            long,
+            conflicts_with = "total",
+            help = "Divide the counts of each group by the total length (bp) of the nodes it traverses, giving density-style values that are comparable between groups of different size (not supported when counting edges)"
+        )]
+        normalize_by_length: bool,
 
         #[clap(
-            short = 'H',
             long,
-            help = "Merge counts from paths belonging to same haplotype"
+            conflicts_with_all = &["total", "normalize_by_length", "count_types"],
+            help = "Divide the counts contributed by each path by the length of that path (its number of nodes or edges, or its bp when counting bp) before summing them up per group, so that long and short paths contribute comparably; a group thus reports the sum of the fractions of its paths, and a group of k paths sums to k over all countables"
+        )]
+        count_coverage_as_fraction_of_path_length: bool,
+        #[clap(
+            long,
+            help = "Break PanSN-style group names (sample#haplotype#seqid) into separate sample, haplotype, and seqid fields, reported as header rows above the group columns; fields missing from a group name, e.g., when grouping by sample, are left empty",
+            conflicts_with = "total"
+        )]
+        split_group_columns: bool,
+    },
+
+    #[clap(
+        about = "Estimate the expected number of countables in a random selection of a given number of groups (rarefaction) by repeated sampling"
+    )]
+    Rarefy {
+        #[clap(short, long,
+            help = "Graph quantity to be counted",
+            default_value = "node",
+            ignore_case = true,
+            value_parser = clap_enum_variants!(CountType),
         )]
-        groupby_haplotype: bool,
+        count: CountType,
+
+        #[clap(flatten)]
+        abacus_args: AbacusArgs,
 
         #[clap(
-            short = 'S',
+            short = 'm',
             long,
-            help = "Merge counts from paths belonging to same sample"
+            help = "Number of groups drawn (without replacement) in each replicate"
         )]
-        groupby_sample: bool,
+        size: usize,
 
         #[clap(
             short,
             long,
-            help = "Run in parallel on N threads",
-            default_value = "1"
+            help = "Number of random selections of groups from which mean and variance are estimated",
+            default_value = "100"
         )]
-        threads: usize,
+        replicates: usize,
     },
-}
 
-pub fn parse_threshold_cli(
-    threshold_str: &str,
-    require: RequireThreshold,
-) -> Result<Vec<Threshold>, std::io::Error> {
-    let mut thresholds = Vec::new();
+    #[clap(
+        about = "Report for each group the number of countables it traverses, and how many of them are shared with other groups or private to it"
+    )]
+    Attribution {
+        #[clap(short, long,
+            help = "Graph quantity to be counted",
+            default_value = "bp",
+            ignore_case = true,
+            value_parser = clap_enum_variants!(CountType),
+        )]
+        count: CountType,
 
-    for (i, el) in threshold_str.split(',').enumerate() {
-        let rel_val = match f64::from_str(el.trim()) {
-            Ok(t) => {
-                if 0.0 <= t && t <= 1.0 {
-                    Ok(t)
-                } else {
-                    Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!(
-                            "relative threshold \"{}\" ({}. element in list) must be within [0,1].",
-                            &threshold_str,
-                            i + 1
-                        ),
-                    ))
-                }
-            }
-            Err(_) => Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!(
-                    "threshold \"{}\" ({}. element in list) is required to be float, but isn't.",
-                    &threshold_str,
-                    i + 1
-                ),
-            )),
-        };
+        #[clap(flatten)]
+        abacus_args: AbacusArgs,
 
-        thresholds.push(
-            match require {
-                RequireThreshold::Absolute => Threshold::Absolute(usize::from_str(el.trim()).map_err(|_|
-                    std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            format!("threshold \"{}\" ({}. element in list) is required to be integer, but isn't.",
-                    &threshold_str,
-                    i + 1)))?),
-            RequireThreshold::Relative => Threshold::Relative(rel_val?),
-            RequireThreshold::Either =>
-        if let Some(t) = usize::from_str(el.trim()).ok() {
-            Threshold::Absolute(t)
-        } else {
-            Threshold::Relative(rel_val?)
-            }
-            }
-            );
-    }
-    Ok(thresholds)
-}
+        #[clap(
+            long,
+            help = "Divide the counts of each group by the total length (bp) of the nodes it traverses, giving density-style values that are comparable between groups of different size (not supported when counting edges)"
+        )]
+        normalize_by_length: bool,
+        #[clap(
+            long,
+            help = "Break PanSN-style group names (sample#haplotype#seqid) into separate sample, haplotype, and seqid columns following the group column; fields missing from a group name, e.g., when grouping by sample, are left empty"
+        )]
+        split_group_columns: bool,
+    },
 
-pub fn read_params() -> Params {
-    Command::parse().cmd
-}
+    #[clap(about = "Report the coverage of a single item and the groups that cover it")]
+    Item {
+        #[clap(short, long,
+            help = "Graph quantity to be counted",
+            default_value = "node",
+            ignore_case = true,
+            value_parser = clap_enum_variants!(CountType),
+        )]
+        count: CountType,
 
-pub fn run<W: Write>(params: Params, out: &mut BufWriter<W>) -> Result<(), std::io::Error> {
-    // set the number of threads used in parallel computation
-    if let Params::Histgrowth { threads, .. }
-    | Params::Hist { threads, .. }
-    | Params::OrderedHistgrowth { threads, .. }
-    | Params::Table { threads, .. } = params
-    {
-        if threads > 0 {
-            log::info!("running panacus on {} threads", &threads);
-            rayon::ThreadPoolBuilder::new()
-                .num_threads(threads)
-                .build_global()
-                .unwrap();
-        } else {
-            log::info!("running panacus using all available CPUs");
-            rayon::ThreadPoolBuilder::new().build_global().unwrap();
-        }
-    }
+        #[clap(flatten)]
+        abacus_args: AbacusArgs,
 
-    // make sure either group, groupby-sample, or groupby-haplotype is set
-    if let Params::Histgrowth {
-        groupby,
-        groupby_haplotype,
-        groupby_sample,
-        ..
-    }
-    | Params::Hist {
-        groupby,
-        groupby_haplotype,
-        groupby_sample,
-        ..
-    }
-    | Params::OrderedHistgrowth {
-        groupby,
-        groupby_haplotype,
-        groupby_sample,
-        ..
-    }
-    | Params::Table {
-        groupby,
-        groupby_haplotype,
-        groupby_sample,
-        ..
-    } = &params
-    {
-        let mut c = 0;
-        if !groupby.is_empty() {
-            c += 1;
-        }
-        if *groupby_haplotype {
-            c += 1;
-        }
-        if *groupby_sample {
-            c += 1
-        }
-        if c > 1 {
-            let msg = "At most one option of groupby, groupby-haplotype, and groupby-sample can be set at once, but at least two are given.";
-            log::error!("{}", &msg);
-            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
-        }
-    }
+        #[clap(
+            long,
+            help = "ID of the item, i.e., a segment ID or, when counting edges, an edge of the form >u<v as reported by the table command",
+            required = true
+        )]
+        id: String,
+    },
 
-    //
-    // 1st step: loading data from group / subset / exclude files and indexing graph
+    #[clap(
+        about = "Report the number of countables present in one but not the other of two groups, and in both of them"
+    )]
+    Compare {
+        #[clap(short, long,
+            help = "Graph quantity to be counted",
+            default_value = "node",
+            ignore_case = true,
+            value_parser = clap_enum_variants!(CountType),
+        )]
+        count: CountType,
+
+        #[clap(flatten)]
+        abacus_args: AbacusArgs,
+
+        #[clap(long, help = "Name of the first group", required = true)]
+        a: String,
+
+        #[clap(long, help = "Name of the second group", required = true)]
+        b: String,
+
+        #[clap(
+            long,
+            help = "Additionally report the total length (bp) of the countables in each category (ignored when counting edges)"
+        )]
+        with_bp: bool,
+    },
+
+    #[clap(
+        about = "Report the countables whose presence varies most across groups, i.e., whose coverage is closest to half the number of groups"
+    )]
+    Hotspots {
+        #[clap(short, long,
+        help = "Graph quantity to be counted",
+        default_value = "node",
+        ignore_case = true,
+        value_parser = clap_enum_variants!(CountType),
+    )]
+        count: CountType,
+
+        #[clap(flatten)]
+        abacus_args: AbacusArgs,
+
+        #[clap(
+            short = 'n',
+            long,
+            help = "Report the N countables with the highest score",
+            default_value = "10"
+        )]
+        top: usize,
+
+        #[clap(
+            long,
+            help = "Score by which countables are ranked, both of which are maximal if half of the groups cover a countable: \"variance\" is the binary variance p(1-p), \"entropy\" the binary entropy -p log2(p) - (1-p) log2(1-p), where p is the fraction of groups that cover the countable",
+            default_value = "variance",
+            ignore_case = true,
+            value_parser = clap_enum_variants!(HotspotScore),
+        )]
+        score: HotspotScore,
+    },
+
+    #[clap(
+        about = "Report edges that are traversed by paths/walks but have no corresponding L-line, and vice versa, as well as L-lines that refer to undefined segments"
+    )]
+    EdgeAudit {
+        #[clap(flatten)]
+        graph_args: GraphArgs,
+    },
+
+    #[clap(
+        about = "Report the intervals of a reference path that are traversed by no group other than the reference's own as BED"
+    )]
+    PrivateBed {
+        #[clap(flatten)]
+        abacus_args: AbacusArgs,
+
+        #[clap(
+            name = "ref",
+            long = "ref",
+            help = "Reference path whose private intervals are reported",
+            required = true
+        )]
+        reference: String,
+    },
+
+    #[clap(
+        about = "Extract the subgraph of nodes traversed by a minimum number of groups, along with the L-lines between them, in GFA1 format; paths and walks are cut into the stretches that traverse retained nodes only"
+    )]
+    Subgraph {
+        #[clap(flatten)]
+        abacus_args: AbacusArgs,
+
+        #[clap(
+            short = 'c',
+            long,
+            help = "Retain only nodes that are traversed by at least N groups",
+            required = true
+        )]
+        min_coverage: usize,
+
+        #[clap(
+            short,
+            long,
+            help = "Write the subgraph to the given file instead of standard output",
+            default_value = ""
+        )]
+        output: String,
+    },
+
+    #[clap(
+        about = "Report the distribution of node lengths as histogram, i.e., the number of nodes and their total length per length bin"
+    )]
+    NodeLengths {
+        #[clap(flatten)]
+        graph_args: GraphArgs,
+
+        #[clap(
+            long,
+            help = "Width of the length bins",
+            default_value = "1",
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        bin_width: usize,
+
+        #[clap(
+            long,
+            conflicts_with = "bin_width",
+            help = "Use logarithmic length bins [1,1], [2,3], [4,7], ... (powers of two) instead of bins of equal width"
+        )]
+        log_bins: bool,
+
+        #[clap(
+            long,
+            help = "Do not count soft-masked (lowercase) bases toward the length of nodes"
+        )]
+        ignore_softmask: bool,
+    },
+
+    #[clap(
+        about = "Measure run time and peak memory of indexing, counting, histogram, and growth on a synthetic random graph, independent of any input data; compare runs with different --threads and --shards to assess parallel speedup and sharding of the item table"
+    )]
+    Bench {
+        #[clap(
+            long,
+            help = "Number of nodes of the synthetic graph",
+            default_value = "100000"
+        )]
+        nodes: usize,
+
+        #[clap(
+            long,
+            help = "Number of paths of the synthetic graph",
+            default_value = "10"
+        )]
+        paths: usize,
+
+        #[clap(
+            long,
+            help = "Seed of the random number generator used to generate the graph",
+            default_value = "42"
+        )]
+        seed: u64,
+
+        #[clap(
+            long,
+            help = "Number of shards of the item table [default: adapted to the number of nodes]",
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        shards: Option<usize>,
+
+        #[clap(flatten)]
+        parse_args: ParseArgs,
+
+        #[clap(
+            short,
+            long,
+            help = "Run in parallel on N threads",
+            default_value = "1"
+        )]
+        threads: usize,
+    },
+
+    #[clap(about = "Merge coverage histograms into one table with a column per histogram")]
+    HistMerge {
+        #[clap(
+            index = 1,
+            help = "Coverage histograms as tab-separated value (tsv) files",
+            required = true
+        )]
+        hist_files: Vec<String>,
+
+        #[clap(
+            long,
+            help = "Column names of the form <name1>,<name2>,... (one per histogram); if not given, file names are used",
+            value_delimiter = ','
+        )]
+        names: Vec<String>,
+    },
+
+    #[clap(
+        about = "Report the number of countables with a given coverage (or coverage range) from a coverage histogram"
+    )]
+    AtCoverage {
+        #[clap(
+            index = 1,
+            help = "Coverage histogram as tab-separated value (tsv) file",
+            required = true
+        )]
+        hist_file: String,
+
+        #[clap(
+            short,
+            long,
+            help = "Coverage level of the form <level> or <from>-<to> (both inclusive); countables of all coverage levels within the range are summed up",
+            required = true
+        )]
+        level: String,
+    },
+
+    #[clap(
+        about = "Estimate the total number of countables, including those not yet seen, from the singletons and doubletons of a coverage histogram using the Chao1 and ACE richness estimators"
+    )]
+    Estimate {
+        #[clap(
+            index = 1,
+            help = "Coverage histogram as tab-separated value (tsv) file",
+            required = true
+        )]
+        hist_file: String,
+
+        #[clap(
+            long,
+            help = "Countables with coverage up to this value are considered rare by the ACE estimator",
+            default_value = "10"
+        )]
+        rare_threshold: usize,
+    },
+}
+
+impl Params {
+    // options of commands that compute coverage over the paths of a graph
+    pub fn abacus_args(&self) -> Option<&AbacusArgs> {
+        match self {
+            Params::Histgrowth { abacus_args, .. }
+            | Params::Report { abacus_args, .. }
+            | Params::Hist { abacus_args, .. }
+            | Params::Hotspots { abacus_args, .. }
+            | Params::OrderedHistgrowth { abacus_args, .. }
+            | Params::Table { abacus_args, .. }
+            | Params::Rarefy { abacus_args, .. }
+            | Params::Attribution { abacus_args, .. }
+            | Params::Item { abacus_args, .. }
+            | Params::Compare { abacus_args, .. }
+            | Params::PrivateBed { abacus_args, .. }
+            | Params::Subgraph { abacus_args, .. } => Some(abacus_args),
+            _ => None,
+        }
+    }
+
+    pub fn graph_args(&self) -> Option<&GraphArgs> {
+        match self {
+            Params::EdgeAudit { graph_args, .. } | Params::NodeLengths { graph_args, .. } => {
+                Some(graph_args)
+            }
+            _ => self.abacus_args().map(|x| &x.graph_args),
+        }
+    }
+
+    pub fn group_args(&self) -> Option<&GroupArgs> {
+        self.abacus_args().map(|x| &x.group_args)
+    }
+
+    pub fn growth_args(&self) -> Option<&GrowthArgs> {
+        match self {
+            Params::Histgrowth { growth_args, .. }
+            | Params::Report { growth_args, .. }
+            | Params::Growth { growth_args, .. }
+            | Params::OrderedHistgrowth { growth_args, .. } => Some(growth_args),
+            _ => None,
+        }
+    }
+
+    // count type of commands that compute coverage over the paths of a graph
+    pub fn count(&self) -> Option<CountType> {
+        match self {
+            Params::Histgrowth { count, .. }
+            | Params::Report { count, .. }
+            | Params::Hist { count, .. }
+            | Params::Hotspots { count, .. }
+            | Params::OrderedHistgrowth { count, .. }
+            | Params::Table { count, .. }
+            | Params::Rarefy { count, .. }
+            | Params::Attribution { count, .. }
+            | Params::Item { count, .. }
+            | Params::Compare { count, .. } => Some(*count),
+            // private intervals and subgraphs are determined by the coverage of nodes
+            Params::PrivateBed { .. } | Params::Subgraph { .. } => Some(CountType::Node),
+            _ => None,
+        }
+    }
+
+    pub fn threads(&self) -> Option<usize> {
+        match self {
+            Params::Bench { threads, .. } => Some(*threads),
+            _ => self.graph_args().map(|x| x.threads),
+        }
+    }
+}
+
+// options controlling how segments and their IDs are read from the GFA file
+#[derive(Args, Debug, Clone)]
+pub struct ParseArgs {
+    #[clap(
+        long,
+        help = "Strip given prefix from node IDs when indexing the graph (affects S, L, P, and W lines alike); node IDs are reported without the prefix in all outputs",
+        default_value = ""
+    )]
+    pub strip_prefix: String,
+
+    #[clap(
+        long,
+        help = "Apply URL-style decoding (e.g., %09 for tab) to segment and path IDs before matching them (affects S, L, P, and W lines alike)"
+    )]
+    pub decode_names: bool,
+
+    #[clap(
+        long,
+        help = "Normalize numeric segment IDs by removing leading zeros (e.g., 007 becomes 7) before matching them (affects S, L, P, and W lines alike)"
+    )]
+    pub normalize_ids: bool,
+
+    #[clap(
+        long,
+        help = "How to deal with segments that have no sequence (\"*\") and no LN:i: tag, i.e., whose length is unknown: \"error\" stops, \"skip\" assigns them length 0 so that they contribute no bp (they are still counted as nodes and edges), and \"one\" assumes length 1. Defaults to error when counting bp and to one otherwise",
+        ignore_case = true,
+        value_parser = clap_enum_variants!(MissingLength),
+    )]
+    pub on_missing_length: Option<MissingLength>,
+}
+
+// input graph along with the options to read and index it
+#[derive(Args, Debug, Clone)]
+pub struct GraphArgs {
+    #[clap(index = 1, help = "graph in GFA1 format", required = true)]
+    pub gfa_file: String,
+
+    #[clap(flatten)]
+    pub parse_args: ParseArgs,
+
+    #[clap(
+        long,
+        help = "File with additional path definitions that are processed as if they were part of the GFA file, one per line, either as GFA P/W-line or of the form <name><tab><node list as in P-lines>",
+        default_value = ""
+    )]
+    pub paths_file: String,
+
+    #[clap(
+        short,
+        long,
+        help = "Run in parallel on N threads",
+        default_value = "1"
+    )]
+    pub threads: usize,
+}
+
+// options assigning paths to groups
+#[derive(Args, Debug, Clone)]
+pub struct GroupArgs {
+    #[clap(
+        short,
+        long,
+        help = "Merge counts from paths by path-group mapping from given tab-separated two-column file",
+        default_value = ""
+    )]
+    pub groupby: String,
+
+    #[clap(
+        short = 'H',
+        long,
+        help = "Merge counts from paths belonging to same haplotype"
+    )]
+    pub groupby_haplotype: bool,
+
+    #[clap(
+        short = 'S',
+        long,
+        help = "Merge counts from paths belonging to same sample"
+    )]
+    pub groupby_sample: bool,
+
+    #[clap(
+        long,
+        help = "Merge groups whose names differ only by case or leading/trailing whitespace (e.g., as derived from paths with different naming conventions) into the group named like the first of them; without this setting, such groups are reported but kept apart"
+    )]
+    pub normalize_group_names: bool,
+}
+
+// options shared by all commands that compute coverage over the paths of a graph
+#[derive(Args, Debug, Clone)]
+pub struct AbacusArgs {
+    #[clap(flatten)]
+    pub graph_args: GraphArgs,
+
+    #[clap(flatten)]
+    pub group_args: GroupArgs,
+
+    #[clap(
+        name = "subset",
+        short,
+        long,
+        help = "Produce counts by subsetting the graph to a given list of paths (1-column list) or path coordinates (3- or 12-column BED file, or GFF3 file with extension .gff/.gff3; optionally gzip-compressed)",
+        default_value = ""
+    )]
+    pub positive_list: String,
+
+    #[clap(
+        name = "exclude",
+        short,
+        long,
+        help = "Exclude bp/node/edge from counting that intersect with paths (1-column list) or path coordinates (3- or 12-column BED-file, or GFF3 file with extension .gff/.gff3; optionally gzip-compressed) provided by the given file; all intersecting bp/node/edge will be exluded also in other paths not part of the given list",
+        default_value = ""
+    )]
+    pub negative_list: String,
+
+    #[clap(
+        long,
+        help = "Randomly keep only the given fraction F in (0,1] of the groups (after applying grouping and subset instructions) for a quick preview"
+    )]
+    pub subsample: Option<f64>,
+
+    #[clap(
+        long,
+        help = "Seed of the random number generator used for subsampling groups (and, depending on the command, items or rarefaction replicates)"
+    )]
+    pub seed: Option<u64>,
+
+    #[clap(
+        long,
+        help = "Name of the optional S-line tag (of type i or f) that reports per-node depth; if set, only nodes whose depth lies within [depth-min, depth-max] are counted",
+        default_value = ""
+    )]
+    pub depth_tag: String,
+
+    #[clap(long, help = "Minimum depth of counted nodes (requires depth-tag)")]
+    pub depth_min: Option<f64>,
+
+    #[clap(long, help = "Maximum depth of counted nodes (requires depth-tag)")]
+    pub depth_max: Option<f64>,
+
+    #[clap(
+        long,
+        help = "Use the given path as reference: it is neither counted nor part of any group, but subset/exclude coordinates given for it restrict counting to/exclude the items it visits within these coordinates"
+    )]
+    pub reference: Option<String>,
+
+    #[clap(
+        long,
+        conflicts_with = "reference",
+        help = "Count only items that are not visited by the given path (within its subset coordinates, if given), e.g., to quantify sequence that is novel relative to a reference; the path itself is neither counted nor part of any group"
+    )]
+    pub relative_to: Option<String>,
+
+    #[clap(
+        long,
+        help = "Drop paths/walks shorter than BP, i.e., whose summed node lengths are below BP, from grouping and counting; a group is kept as long as any of its paths passes",
+        default_value = "0"
+    )]
+    pub min_path_length: usize,
+
+    #[clap(
+        long,
+        help = "Identify edges by the pair of nodes they connect, irrespective of orientation, e.g., >1>2, >1<2, and <1>2 are the same edge; by default, an edge and its reverse complement (e.g., >1>2 and <2<1) are the same edge, but edges differing in the orientation of one of their nodes are not. Undirected edges are reported as >u>v. Only relevant when counting edges"
+    )]
+    pub undirected_edges: bool,
+
+    #[clap(
+        long,
+        help = "Do not count soft-masked (lowercase) bases when counting bp; segments without sequence (\"*\") are unaffected, LN:i: tags are not considered. Cannot be combined with subset/exclude coordinates"
+    )]
+    pub ignore_softmask: bool,
+
+    #[clap(
+        long,
+        help = "Report summary statistics of the graph (total bp, average node length, node length N50, number of edges, samples, and haplotypes) after indexing"
+    )]
+    pub graph_stats: bool,
+}
+
+// options of commands that calculate and report growth curves
+#[derive(Args, Debug, Clone)]
+pub struct GrowthArgs {
+    #[clap(
+        short = 'l',
+        long,
+        help = "List of coverage thresholds of the form <level1>,<level2>,..., where integers are absolute thresholds and percentages (e.g., 50%) or fractions within [0,1] are relative to the number of groups. Number of values must be one or match that of quorum setting",
+        default_value = "1"
+    )]
+    pub coverage: String,
+
+    #[clap(
+        short,
+        long,
+        help = "List of quorum fractions (or percentages such as 50%) of the form <level1>,<level2>,... Number of values must be one or match that of coverage setting",
+        default_value = "0"
+    )]
+    pub quorum: String,
+
+    #[clap(
+        long,
+        help = "Additionally report the soft-core growth for the given fraction F in (0,1], i.e., at each step m, count items that are present in at least ceil(F*m) of the m groups; equivalent to adding a coverage/quorum pair of 1/F (for ordered growth, these semantics require --stepwise-thresholds)"
+    )]
+    pub soft_core: Option<f64>,
+
+    #[clap(
+        long,
+        help = "Additionally report the given growth curves: \"union\" counts items present in at least one group (coverage 1, quorum 0), \"core\" counts items present in all groups (coverage 1, quorum 1)",
+        value_delimiter = ',',
+        ignore_case = true,
+        value_parser = clap_enum_variants!(GrowthCurve),
+    )]
+    pub curves: Vec<GrowthCurve>,
+
+    #[clap(
+        long,
+        help = "Tab-separated file with one coverage/quorum pair per line (same syntax as the coverage and quorum settings); replaces the coverage and quorum settings",
+        default_value = ""
+    )]
+    pub thresholds_file: String,
+
+    #[clap(
+        long,
+        help = "Report growth in long (tidy) format, i.e., one row per group and coverage/quorum pair with columns group, coverage, quorum, and value"
+    )]
+    pub long: bool,
+
+    #[clap(
+        long,
+        help = "Report growth as a strictly rectangular table suitable for import into databases, i.e., in long format with columns step, coverage, quorum, group (empty unless groups are ordered), and value, and without comment lines"
+    )]
+    pub sql_friendly: bool,
+
+    #[clap(
+        long,
+        help = "Additionally report the efficiency (growth[k] - growth[k-1]) / growth[k] of each growth step; for the union curve, this is the fraction of countables newly added by the k-th group, which indicates diminishing returns of adding further groups"
+    )]
+    pub efficiency: bool,
+
+    #[clap(
+        long,
+        help = "Rounding of growth values in the output: \"floor\" (default) and \"round\" and \"ceil\" report integers, \"none\" reports the exact (possibly fractional) value with the number of decimal places given by the precision setting",
+        default_value = "floor",
+        ignore_case = true,
+        value_parser = clap_enum_variants!(Rounding),
+    )]
+    pub round: Rounding,
+
+    #[clap(
+        long,
+        help = "Number of decimal places of growth values if rounding is set to \"none\"",
+        default_value = "4"
+    )]
+    pub precision: usize,
+}
+
+pub fn parse_coverage_range(range_str: &str) -> Result<(usize, usize), std::io::Error> {
+    let parse = |x: &str| {
+        usize::from_str(x.trim()).map_err(|_| {
+            let msg = format!(
+                "coverage level \"{}\" must be of the form <level> or <from>-<to>, where both are non-negative integers",
+                range_str
+            );
+            log::error!("{}", &msg);
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, msg)
+        })
+    };
+    let (from, to) = match range_str.split_once('-') {
+        Some((a, b)) => (parse(a)?, parse(b)?),
+        None => {
+            let x = parse(range_str)?;
+            (x, x)
+        }
+    };
+    if from > to {
+        let msg = format!(
+            "coverage range \"{}\" is empty, lower bound must not exceed upper bound",
+            range_str
+        );
+        log::error!("{}", &msg);
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+    }
+    Ok((from, to))
+}
+
+pub fn parse_threshold_cli(
+    threshold_str: &str,
+    require: RequireThreshold,
+) -> Result<Vec<Threshold>, std::io::Error> {
+    let mut thresholds = Vec::new();
+
+    for (i, el) in threshold_str.split(',').enumerate() {
+        // percentages are always relative thresholds
+        if let Some(pct) = el.trim().strip_suffix('%') {
+            let t = f64::from_str(pct.trim()).map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "percentage threshold \"{}\" ({}. element in list) is required to be a number followed by %, but isn't.",
+                        &threshold_str,
+                        i + 1
+                    ),
+                )
+            })?;
+            if !(0.0 <= t && t <= 100.0) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "percentage threshold \"{}\" ({}. element in list) must be within [0%,100%].",
+                        &threshold_str,
+                        i + 1
+                    ),
+                ));
+            }
+            if let RequireThreshold::Absolute = require {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "threshold \"{}\" ({}. element in list) is required to be integer, but is a percentage.",
+                        &threshold_str,
+                        i + 1
+                    ),
+                ));
+            }
+            thresholds.push(Threshold::Relative(t / 100.0));
+            continue;
+        }
+
+        let rel_val = match f64::from_str(el.trim()) {
+            Ok(t) => {
+                if 0.0 <= t && t <= 1.0 {
+                    Ok(t)
+                } else {
+                    Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "relative threshold \"{}\" ({}. element in list) must be within [0,1].",
+                            &threshold_str,
+                            i + 1
+                        ),
+                    ))
+                }
+            }
+            Err(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "threshold \"{}\" ({}. element in list) is required to be float, but isn't.",
+                    &threshold_str,
+                    i + 1
+                ),
+            )),
+        };
+
+        thresholds.push(
+            match require {
+                RequireThreshold::Absolute => Threshold::Absolute(usize::from_str(el.trim()).map_err(|_|
+                    std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            format!("threshold \"{}\" ({}. element in list) is required to be integer, but isn't.",
+                    &threshold_str,
+                    i + 1)))?),
+            RequireThreshold::Relative => Threshold::Relative(rel_val?),
+            RequireThreshold::Either =>
+        if let Some(t) = usize::from_str(el.trim()).ok() {
+            Threshold::Absolute(t)
+        } else {
+            Threshold::Relative(rel_val?)
+            }
+            }
+            );
+    }
+    Ok(thresholds)
+}
+
+impl GroupArgs {
+    // make sure at most one of groupby, groupby-sample, or groupby-haplotype is set
+    fn check(&self) -> Result<(), std::io::Error> {
+        let c = [
+            !self.groupby.is_empty(),
+            self.groupby_haplotype,
+            self.groupby_sample,
+        ]
+        .iter()
+        .filter(|&&x| x)
+        .count();
+        if c > 1 {
+            let msg = "At most one option of groupby, groupby-haplotype, and groupby-sample can be set at once, but at least two are given.";
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+        }
+        Ok(())
+    }
+}
+
+impl ParseArgs {
+    // indexes the graph with the given options, completed by the parse arguments; unless set,
+    // the length of segments of unknown length is determined by the given default
+    fn index_graph<R: std::io::Read>(
+        &self,
+        data: &mut std::io::BufReader<R>,
+        options: IndexOptions,
+    ) -> Result<GraphAuxilliary, std::io::Error> {
+        GraphAuxilliary::from_gfa(
+            data,
+            &IndexOptions {
+                strip_prefix: self.strip_prefix.clone(),
+                decode_names: self.decode_names,
+                normalize_ids: self.normalize_ids,
+                missing_length: self.on_missing_length.unwrap_or(options.missing_length),
+                ..options
+            },
+        )
+    }
+}
+
+impl GraphArgs {
+    // size of the data read in each pass over the GFA file
+    fn input_bytes(&self) -> Result<u64, std::io::Error> {
+        let mut b = fs::metadata(&self.gfa_file)?.len();
+        if !self.paths_file.is_empty() {
+            b += fs::metadata(&self.paths_file)?.len();
+        }
+        Ok(b)
+    }
+
+    // opens the GFA file, followed by the paths of the paths file, if given
+    fn open(&self) -> Result<std::io::BufReader<Box<dyn std::io::Read>>, std::io::Error> {
+        io::open_gfa(&self.gfa_file, &self.paths_file)
+    }
+}
+
+// global settings of a run that are independent of the command
+#[derive(Debug, Clone, Default)]
+pub struct RunConfig {
+    pub strict: bool,
+    pub stats_file: Option<String>,
+    pub metrics_file: Option<String>,
+    pub print_config: bool,
+}
+
+pub fn read_params() -> (Params, RunConfig) {
+    let command = Command::parse();
+    set_buffer_size(command.buffer_size);
+    (
+        command.cmd,
+        RunConfig {
+            strict: command.strict,
+            stats_file: command.stats_file,
+            metrics_file: command.metrics_file,
+            print_config: command.print_config,
+        },
+    )
+}
+
+pub fn run<W: Write>(
+    mut params: Params,
+    config: RunConfig,
+    out: &mut BufWriter<W>,
+) -> Result<(), std::io::Error> {
+    let strict = config.strict;
+    let mut stats = RunStats::new();
+    stats.add_str(
+        "command",
+        &std::env::args().collect::<Vec<String>>().join(" "),
+    );
+
+    // count types of the tables reported by a table run; the first one takes the place of the
+    // count setting
+    let table_counts: Vec<CountType> = match &mut params {
+        Params::Table {
+            count, count_types, ..
+        } => {
+            if let Some(c) = count_types.first() {
+                *count = *c;
+            }
+            count_types.iter().fold(vec![*count], |mut res, c| {
+                if !res.contains(c) {
+                    res.push(*c);
+                }
+                res
+            })
+        }
+        _ => Vec::new(),
+    };
+    let input_files: Vec<&String> = match &params {
+        Params::Growth { hist_file, .. }
+        | Params::AtCoverage { hist_file, .. }
+        | Params::Estimate { hist_file, .. } => {
+            vec![hist_file]
+        }
+        Params::HistMerge { hist_files, .. } => hist_files.iter().collect(),
+        Params::Bench { .. } => Vec::new(),
+        _ => vec![&params.graph_args().unwrap().gfa_file],
+    };
+    let mut input_bytes = 0;
+    for f in input_files.iter() {
+        input_bytes += fs::metadata(f)?.len();
+    }
+    stats.add_str_list(
+        "input_files",
+        &input_files
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>(),
+    );
+    stats.add_num("input_bytes", input_bytes);
+
+    // set the number of threads used in parallel computation
+    if let Some(threads) = params.threads() {
+        let res = if threads > 0 {
+            log::info!("running panacus on {} threads", &threads);
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build_global()
+        } else {
+            log::info!("running panacus using all available CPUs");
+            rayon::ThreadPoolBuilder::new().build_global()
+        };
+        // the global pool can be initialized only once per process, which fails if run is called
+        // repeatedly, e.g., when panacus is used as a library
+        if let Err(e) = res {
+            warn_or_fail(
+                strict,
+                &format!(
+                    "thread pool is already initialized ({}), continuing on its {} threads",
+                    e,
+                    rayon::current_num_threads()
+                ),
+            )?;
+        }
+    }
+
+    if let Some(group_args) = params.group_args() {
+        group_args.check()?;
+    }
+
+    // node and bp counts can be derived from the same abacus, as long as it is built over nodes
+    if let Params::OrderedHistgrowth {
+        count, dual_count, ..
+    } = &params
+    {
+        if !dual_count.is_empty()
+            && (count != &CountType::Node
+                || dual_count
+                    .iter()
+                    .any(|t| t != &CountType::Node && t != &CountType::Bp))
+        {
+            let msg =
+                "dual-count supports only count types node and bp and requires count type node";
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+        }
+    }
+
+    // size of the data read in each pass over the GFA file, used to report throughput
+    let gfa_bytes = match params.graph_args() {
+        Some(graph_args) => Some(graph_args.input_bytes()?),
+        None => None,
+    };
+
+    //
+    // 1st step: loading data from group / subset / exclude files and indexing graph
+    //
+    let timer = Instant::now();
+    //
+    let (graph_aux, abacus_aux) = match (params.abacus_args(), params.count()) {
+        (Some(args), Some(count)) => {
+            match index_graph(&params, args, count, &table_counts, strict, &mut stats)? {
+                Some((graph_aux, abacus_aux)) => (Some(graph_aux), Some(abacus_aux)),
+                // graph does not contain any paths
+                None => return Ok(()),
+            }
+        }
+        _ => (None, None),
+    };
+    if let (Some(b), Some(args)) = (gfa_bytes, params.abacus_args()) {
+        // the length of paths/walks is measured in an additional pass
+        let passes = if args.min_path_length > 0 { 2 } else { 1 };
+        log_throughput("indexing", b * passes, timer.elapsed());
+    }
+    stats.add_timing("indexing", timer.elapsed());
+
+    // coverage/quorum thresholds are resolved before counting, so that the configuration can be
+    // reviewed before waiting for the results
+    let hist_aux = match params {
+        Params::Histgrowth { .. }
+        | Params::Report { .. }
+        | Params::Growth { .. }
+        | Params::OrderedHistgrowth { .. } => {
+            let hist_aux = HistAuxilliary::from_params(&params)?;
+            stats.add_str_list(
+                "coverage",
+                &hist_aux
+                    .coverage
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<String>>(),
+            );
+            stats.add_str_list(
+                "quorum",
+                &hist_aux
+                    .quorum
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<String>>(),
+            );
+            Some(hist_aux)
+        }
+        _ => None,
+    };
+
+    if config.print_config {
+        eprintln!("{}", stats.config_to_json());
+    }
+
+    //
+    // 2nd step: build abacus or calculate coverage table
+    //
+    let timer = Instant::now();
+
+    // weights of groups as given in the order list of an ordered growth
+    let order_weights = abacus_aux.as_ref().and_then(|x| x.order_weights.clone());
+    // tables of the further count types of a table run
+    let (mut abacus, more_tables) = match (graph_aux, abacus_aux) {
+        (Some(graph_aux), Some(abacus_aux)) => {
+            build_abacus(&params, graph_aux, abacus_aux, &table_counts, &mut stats)?
+        }
+        _ => (Abacus::Nil, Vec::new()),
+    };
+    check_abacus(&abacus)?;
+    if let (Some(b), false) = (gfa_bytes, matches!(abacus, Abacus::Nil)) {
+        log_throughput("counting", b, timer.elapsed());
+    }
+    stats.add_timing("abacus", timer.elapsed());
+
+    //
+    // 3rd step: build histograam
+    //
+    let timer = Instant::now();
+    let hist = build_hist(&params, &abacus, &mut stats)?;
+    stats.add_timing("histogram", timer.elapsed());
+
+    //
+    // 4th step: calculation & output of growth curve / output of histogram
     //
     //
-    let (graph_aux, abacus_aux) = match &params {
+    let timer = Instant::now();
+    let html = matches!(
+        params,
         Params::Histgrowth {
-            gfa_file, count, ..
+            output_format: OutputFormat::Html,
+            ..
+        } | Params::Report { .. }
+    );
+    let sql_friendly = params.growth_args().map_or(false, |x| x.sql_friendly);
+    // GFA output of subgraph is not preceded by the command line
+    if !html && !sql_friendly && !matches!(params, Params::Subgraph { .. }) {
+        writeln!(
+            out,
+            "# {}",
+            std::env::args().collect::<Vec<String>>().join(" ")
+        )?;
+    }
+
+    //    if let Abacus::Group(abacus_group) = &abacus {
+    //        abacus_group.write_rcv(out)?;
+    //        out.flush()?;
+    //        std::process::exit(0x0100);
+    //    }
+
+    match &params {
+        Params::Growth {
+            fit_model: Some(FitModel::TettelinCore),
+            ..
+        } => {
+            write_model_fit(hist.as_ref().unwrap(), out)?;
+        }
+        Params::OrderedHistgrowth {
+            bootstrap: Some(replicates),
+            abacus_args,
+            stepwise_thresholds,
+            ..
+        } => {
+            if let Abacus::Group(abacus_group) = &abacus {
+                run_bootstrap(
+                    abacus_group,
+                    hist_aux.as_ref().unwrap(),
+                    *replicates,
+                    abacus_args.seed,
+                    *stepwise_thresholds,
+                    out,
+                )?;
+            }
+        }
+        Params::Histgrowth { .. } | Params::Growth { .. } | Params::OrderedHistgrowth { .. } => {
+            write_growth(
+                &params,
+                hist.as_ref(),
+                hist_aux.as_ref().unwrap(),
+                &mut abacus,
+                order_weights,
+                html,
+                strict,
+                out,
+            )?;
         }
-        | Params::Hist {
-            gfa_file, count, ..
+        Params::Report { output, .. } if !output.is_empty() => {
+            log::info!("writing report to {}", output);
+            let mut report = BufWriter::new(fs::File::create(output)?);
+            write_growth(
+                &params,
+                hist.as_ref(),
+                hist_aux.as_ref().unwrap(),
+                &mut abacus,
+                order_weights,
+                html,
+                strict,
+                &mut report,
+            )?;
+            report.flush()?;
+        }
+        Params::Report { .. } => {
+            write_growth(
+                &params,
+                hist.as_ref(),
+                hist_aux.as_ref().unwrap(),
+                &mut abacus,
+                order_weights,
+                html,
+                strict,
+                out,
+            )?;
         }
-        | Params::OrderedHistgrowth {
-            gfa_file, count, ..
+        Params::EdgeAudit { graph_args } => {
+            run_edge_audit(graph_args, strict, out)?;
         }
-        | Params::Table {
-            gfa_file, count, ..
+        Params::PrivateBed {
+            abacus_args,
+            reference,
         } => {
-            log::info!("constructing indexes for node/edge IDs, node lengths, and P/W lines..");
-            let mut data = std::io::BufReader::new(fs::File::open(&gfa_file)?);
-            let graph_aux = GraphAuxilliary::from_gfa(&mut data, count == &CountType::Edge)?;
-            log::info!(
-                "..done; found {} paths/walks and {} nodes{}",
-                graph_aux.path_segments.len(),
-                graph_aux.node2id.len(),
-                if let Some(edge2id) = &graph_aux.edge2id {
-                    format!(" {} edges", edge2id.len())
-                } else {
-                    String::new()
+            if let Abacus::Total(abacus_total) = &abacus {
+                run_private_bed(abacus_total, &abacus_args.graph_args, reference, out)?;
+            }
+        }
+        Params::Subgraph {
+            abacus_args,
+            min_coverage,
+            output,
+        } => {
+            if let Abacus::Total(abacus_total) = &abacus {
+                run_subgraph(
+                    abacus_total,
+                    &abacus_args.graph_args,
+                    *min_coverage,
+                    output,
+                    out,
+                )?;
+            }
+        }
+        Params::NodeLengths {
+            graph_args,
+            bin_width,
+            log_bins,
+            ignore_softmask,
+        } => {
+            run_node_lengths(
+                graph_args,
+                *bin_width,
+                *log_bins,
+                *ignore_softmask,
+                strict,
+                out,
+            )?;
+        }
+        Params::Bench {
+            nodes,
+            paths,
+            seed,
+            shards,
+            parse_args,
+            ..
+        } => {
+            run_bench(*nodes, *paths, *seed, *shards, parse_args, strict, out)?;
+        }
+        Params::HistMerge { hist_files, names } => {
+            run_hist_merge(hist_files, names, out)?;
+        }
+        Params::AtCoverage { hist_file, level } => {
+            let (from, to) = parse_coverage_range(level)?;
+            let hist = load_hist(hist_file)?;
+            log::info!("reporting countables with coverage {}..{}", from, to);
+            hist.at_coverage_to_tsv(from, to, out)?;
+        }
+        Params::Estimate {
+            hist_file,
+            rare_threshold,
+        } => {
+            let hist = load_hist(hist_file)?;
+            log::info!("reporting richness estimates");
+            hist.estimate_to_tsv(*rare_threshold, out)?;
+        }
+        Params::Hist {
+            all_cumulatives,
+            min_coverage_bin,
+            list_items,
+            multiplicity,
+            ..
+        } => {
+            if let (false, Abacus::Total(abacus_total)) = (list_items.is_empty(), &abacus) {
+                abacus_total.write_item_lists(list_items)?;
+            }
+            hist.unwrap()
+                .to_tsv(*all_cumulatives, *min_coverage_bin, *multiplicity, out)?;
+        }
+        Params::Hotspots { top, score, .. } => {
+            if let Abacus::Total(abacus_total) = abacus {
+                abacus_total.hotspots_to_tsv(*top, *score, out)?;
+            }
+        }
+        Params::Table {
+            total,
+            with_length,
+            normalize_by_length,
+            head,
+            group_rename,
+            split_group_columns,
+            ..
+        } => {
+            if let Abacus::Group(abacus_group) = abacus {
+                let sections = !more_tables.is_empty();
+                for mut abacus_group in std::iter::once(abacus_group).chain(more_tables) {
+                    if !group_rename.is_empty() {
+                        abacus_group.rename_groups(group_rename)?;
+                    }
+                    log::info!("reporting {} coverage table", abacus_group.count);
+                    if sections {
+                        writeln!(out, "# {}", abacus_group.count)?;
+                    }
+                    abacus_group.to_tsv(
+                        *total,
+                        *with_length,
+                        *normalize_by_length,
+                        *head,
+                        *split_group_columns,
+                        out,
+                    )?;
                 }
+            }
+        }
+        Params::Rarefy {
+            size,
+            replicates,
+            abacus_args,
+            ..
+        } => {
+            if let Abacus::Group(abacus_group) = abacus {
+                run_rarefy(&abacus_group, *size, *replicates, abacus_args.seed, out)?;
+            }
+        }
+        Params::Attribution {
+            normalize_by_length,
+            split_group_columns,
+            ..
+        } => {
+            if let Abacus::Group(abacus_group) = abacus {
+                log::info!("reporting shared and private countables of each group");
+                abacus_group.attribution_to_tsv(*normalize_by_length, *split_group_columns, out)?;
+            }
+        }
+        Params::Compare { a, b, with_bp, .. } => {
+            if let Abacus::Group(abacus_group) = abacus {
+                log::info!(
+                    "comparing {}s of group {} (a) and group {} (b)",
+                    abacus_group.count,
+                    a,
+                    b
+                );
+                abacus_group.compare_to_tsv(a, b, *with_bp, out)?;
+            }
+        }
+        Params::Item { id, .. } => {
+            if let Abacus::Group(abacus_group) = abacus {
+                run_item(&abacus_group, id, out)?;
+            }
+        }
+    };
+    stats.add_timing("output", timer.elapsed());
+
+    if let Some(f) = config.stats_file {
+        log::info!("writing run statistics to {}", &f);
+        fs::write(&f, stats.to_json())?;
+    }
+
+    if let Some(f) = config.metrics_file {
+        log::info!("writing metrics to {}", &f);
+        fs::write(&f, stats.to_prometheus())?;
+    }
+
+    Ok(())
+}
+
+// indexes the graph and loads group / subset / exclude files; returns nothing if the graph does
+// not contain any paths
+fn index_graph(
+    params: &Params,
+    args: &AbacusArgs,
+    count: CountType,
+    table_counts: &[CountType],
+    strict: bool,
+    stats: &mut RunStats,
+) -> Result<Option<(GraphAuxilliary, AbacusAuxilliary)>, std::io::Error> {
+    let AbacusArgs {
+        graph_args,
+        graph_stats,
+        ignore_softmask,
+        depth_tag,
+        undirected_edges,
+        ..
+    } = args;
+    log::info!("constructing indexes for node/edge IDs, node lengths, and P/W lines..");
+    let mut data = graph_args.open()?;
+    if *ignore_softmask && count != CountType::Bp {
+        warn_or_fail(
+            strict,
+            "soft-masked bases are only relevant when counting bp, ignoring",
+        )?;
+    }
+    let mut graph_aux = graph_args.parse_args.index_graph(
+        &mut data,
+        IndexOptions {
+            index_edges: count.requires_edge_index()
+                || table_counts.iter().any(|c| c.requires_edge_index()),
+            ignore_softmask: *ignore_softmask && count == CountType::Bp,
+            depth_tag: depth_tag.clone(),
+            missing_length: if count == CountType::Bp || table_counts.contains(&CountType::Bp) {
+                MissingLength::Error
+            } else {
+                MissingLength::One
+            },
+            strict,
+            ..Default::default()
+        },
+    )?;
+    if *undirected_edges {
+        if count == CountType::Allele || table_counts.contains(&CountType::Allele) {
+            let msg = "undirected edges cannot be combined with counting alleles, which requires edge orientation";
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+        }
+        if count != CountType::Edge && !table_counts.contains(&CountType::Edge) {
+            warn_or_fail(
+                strict,
+                "undirected edges are only relevant when counting edges, ignoring",
+            )?;
+        } else {
+            let c = graph_aux.edge_count;
+            let u = graph_aux.set_undirected_edges();
+            log::info!("merged {} L-lines into {} undirected edges", c, u);
+        }
+    }
+    if count == CountType::Edge || table_counts.contains(&CountType::Edge) {
+        // paths/walks may traverse edges that are not represented by L-lines
+        let c = graph_aux.add_path_edges();
+        if c > 0 {
+            log::info!(
+                "indexed {} edges traversed by paths/walks without corresponding L-line",
+                c
             );
+        }
+    }
+    log::info!(
+        "..done; found {} paths/walks and {} nodes{}",
+        graph_aux.path_segments.len(),
+        graph_aux.node2id.len(),
+        if let Some(edge2id) = &graph_aux.edge2id {
+            format!(" {} edges", edge2id.len())
+        } else {
+            String::new()
+        }
+    );
 
-            if graph_aux.path_segments.len() == 0 {
-                log::error!("there's nothing to do--graph does not contain any annotated paths (P/W lines), exiting");
-                return Ok(());
-            }
+    if *graph_stats {
+        log::info!("{}", graph_aux.stats_summary());
+    }
 
-            log::info!("loading data from group / subset / exclude files");
-            let abacus_aux = AbacusAuxilliary::from_params(&params, &graph_aux)?;
+    if graph_aux.path_segments.len() == 0 {
+        log::error!("there's nothing to do--graph does not contain any annotated paths (P/W lines), exiting; paths can also be provided in a separate file via the paths-file option");
+        return Ok(None);
+    }
 
-            (Some(graph_aux), Some(abacus_aux))
-        }
-        _ => (None, None),
-    };
+    log::info!("loading data from group / subset / exclude files");
+    let abacus_aux = AbacusAuxilliary::from_params(params, &graph_aux)?;
+
+    if *ignore_softmask
+        && count == CountType::Bp
+        && abacus_aux
+            .include_coords
+            .iter()
+            .chain(abacus_aux.exclude_coords.iter())
+            .flatten()
+            .any(|x| x.coords().is_some())
+    {
+        let msg = "ignoring soft-masked bases cannot be combined with subset/exclude coordinates, because node lengths no longer correspond to path positions";
+        log::error!("{}", &msg);
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+    }
 
-    //
-    // 2nd step: build abacus or calculate coverage table
-    //
+    stats.add_str("count", &count.to_string());
+    stats.add_num("paths", graph_aux.path_segments.len());
+    stats.add_num("nodes", graph_aux.node_count);
+    if graph_aux.edge2id.is_some() {
+        stats.add_num("edges", graph_aux.edge_count);
+    }
+    stats.add_num("groups", abacus_aux.count_groups());
+
+    Ok(Some((graph_aux, abacus_aux)))
+}
 
-    let abacus: Abacus = match &params {
-        Params::Histgrowth { gfa_file, .. } | Params::Hist { gfa_file, .. } => {
+// builds the abacus of the command, along with the tables of the further count types of a table
+// run
+fn build_abacus(
+    params: &Params,
+    graph_aux: GraphAuxilliary,
+    abacus_aux: AbacusAuxilliary,
+    table_counts: &[CountType],
+    stats: &mut RunStats,
+) -> Result<(Abacus, Vec<AbacusByGroup>), std::io::Error> {
+    let GraphArgs {
+        gfa_file,
+        paths_file,
+        ..
+    } = match params.graph_args() {
+        Some(graph_args) => graph_args,
+        None => return Ok((Abacus::Nil, Vec::new())),
+    };
+    let mut more_tables: Vec<AbacusByGroup> = Vec::new();
+    let abacus = match params {
+        Params::Histgrowth { .. }
+        | Params::Report { .. }
+        | Params::Hist { .. }
+        | Params::Hotspots { .. }
+        | Params::PrivateBed { .. }
+        | Params::Subgraph { .. } => {
             // creating the abacus from the gfa
 
-            let n_groups = abacus_aux.as_ref().unwrap().count_groups();
+            let n_groups = abacus_aux.count_groups();
             if n_groups > 65534 {
                 return Err(std::io::Error::new(
                     std::io::ErrorKind::Unsupported,
@@ -565,47 +1899,199 @@ pub fn run<W: Write>(params: Params, out: &mut BufWriter<W>) -> Result<(), std::
                 ));
             }
 
+            let (naive_count, accumulate) = match params {
+                Params::Histgrowth {
+                    naive_count,
+                    accumulate,
+                    ..
+                }
+                | Params::Hist {
+                    naive_count,
+                    accumulate,
+                    ..
+                } => (*naive_count, &accumulate[..]),
+                _ => (false, ""),
+            };
+
             log::info!("loading graph from {}", &gfa_file);
-            let mut data = std::io::BufReader::new(fs::File::open(&gfa_file)?);
-            let abacus =
-                AbacusByTotal::from_gfa(&mut data, abacus_aux.unwrap(), graph_aux.unwrap())?;
+            let mut data = io::open_gfa(gfa_file, paths_file)?;
+            let mut abacus = if naive_count {
+                warn_or_fail(
+                    graph_aux.strict,
+                    "counting naively, which is slow and meant for validation only",
+                )?;
+                AbacusByTotal::from_gfa_naive(&mut data, abacus_aux, graph_aux)?
+            } else {
+                AbacusByTotal::from_gfa(&mut data, abacus_aux, graph_aux)?
+            };
+            if !accumulate.is_empty() {
+                abacus.accumulate(accumulate)?;
+            }
             log::info!(
                 "abacus has {} path groups and {} countables",
                 abacus.groups.len(),
                 abacus.countable.len()
             );
+            stats.add_num("countables", abacus.countable.len());
             Abacus::Total(abacus)
         }
-        Params::Table { gfa_file, .. } | Params::OrderedHistgrowth { gfa_file, .. } => {
+        Params::Table { .. }
+        | Params::OrderedHistgrowth { .. }
+        | Params::Rarefy { .. }
+        | Params::Attribution { .. }
+        | Params::Item { .. }
+        | Params::Compare { .. } => {
+            let report_values = if let Params::Table { total, .. } = params {
+                !total
+            } else {
+                false
+            };
+            let path_fractions = matches!(
+                params,
+                Params::Table {
+                    count_coverage_as_fraction_of_path_length: true,
+                    ..
+                }
+            );
+            let rest = if table_counts.len() > 1 {
+                Some((abacus_aux.clone(), graph_aux.clone()))
+            } else {
+                None
+            };
+
             log::info!("loading graph from {}", &gfa_file);
-            let mut data = std::io::BufReader::new(fs::File::open(&gfa_file)?);
+            let mut data = io::open_gfa(gfa_file, paths_file)?;
             let abacus = AbacusByGroup::from_gfa(
                 &mut data,
-                abacus_aux.unwrap(),
-                graph_aux.unwrap(),
-                if let Params::Table { total, .. } = params {
-                    !total
-                } else {
-                    false
-                },
+                abacus_aux,
+                graph_aux,
+                report_values,
+                path_fractions,
             )?;
             log::info!(
                 "abacus has {} path groups and {} countables",
                 abacus.groups.len(),
                 abacus.r.len()
             );
+            stats.add_num("countables", abacus.r.len());
+
+            if let Some((abacus_aux, graph_aux)) = rest {
+                // abaci over nodes serve all node-based count types, except that partially
+                // covered nodes are accounted for only in abaci built for bp counts
+                let no_coords = abacus_aux.include_coords.is_none()
+                    && abacus_aux.exclude_coords.is_none()
+                    && abacus_aux.reference_coords.is_none();
+                let derivable = |a: &AbacusByGroup, c: CountType| {
+                    a.count != CountType::Edge
+                        && c != CountType::Edge
+                        && (no_coords || (a.count != CountType::Bp && c != CountType::Bp))
+                };
+                for &c in table_counts.iter().skip(1) {
+                    let source = std::iter::once(&abacus)
+                        .chain(more_tables.iter())
+                        .find(|a| derivable(a, c))
+                        .cloned();
+                    let table = match source {
+                        Some(mut a) => {
+                            log::info!("deriving {} table from {} table", c, a.count);
+                            a.count = c;
+                            a
+                        }
+                        None => {
+                            log::info!("loading graph from {} for counting {}s", &gfa_file, c);
+                            let mut abacus_aux = abacus_aux.clone();
+                            abacus_aux.count = c;
+                            let mut data = io::open_gfa(gfa_file, paths_file)?;
+                            AbacusByGroup::from_gfa(
+                                &mut data,
+                                abacus_aux,
+                                graph_aux.clone(),
+                                report_values,
+                                // not supported along with several count types
+                                false,
+                            )?
+                        }
+                    };
+                    more_tables.push(table);
+                }
+            }
             Abacus::Group(abacus)
         }
         _ => Abacus::Nil,
     };
+    Ok((abacus, more_tables))
+}
 
-    //
-    // 3rd step: build histograam
-    //
+// warns about anomalies of the coverage computed in the abacus
+fn check_abacus(abacus: &Abacus) -> Result<(), std::io::Error> {
+    // if filtering leaves nothing to count, the output consists of headers and zeros only
+    let empty = match abacus {
+        Abacus::Total(a) => Some((
+            a.countable.iter().skip(1).all(|x| *x == 0),
+            a.graph_aux.number_of_items(&a.count),
+            a.count,
+            a.graph_aux.strict,
+        )),
+        Abacus::Group(a) => Some((
+            a.c.is_empty(),
+            a.graph_aux.number_of_items(&a.count),
+            a.count,
+            a.graph_aux.strict,
+        )),
+        Abacus::Nil => None,
+    };
+    if let Some((true, n, count, strict)) = empty {
+        warn_or_fail(strict, &format!(
+            "all {} {}s are uncovered after applying group, subset, and exclude settings; all reported coverages and growth values will be zero",
+            n, count
+        ))?;
+    }
 
-    let hist: Option<Hist> = match &params {
-        Params::Histgrowth { .. } | Params::Hist { .. } => {
-            if let Abacus::Total(abacus_total) = &abacus {
+    // an item cannot be covered by more groups than there are, unless it is counted repeatedly
+    let overcounted = match abacus {
+        Abacus::Total(a) => Some((a.overcounted_items(), a.groups.len(), a.count, &a.graph_aux)),
+        Abacus::Group(a) => Some((a.overcounted_items(), a.groups.len(), a.count, &a.graph_aux)),
+        Abacus::Nil => None,
+    };
+    if let Some((items, n, count, graph_aux)) = overcounted {
+        if !items.is_empty() {
+            let id2node = graph_aux.id2node();
+            let names: Vec<String> = items
+                .iter()
+                .take(10)
+                .map(|&i| match (count, id2node.get(i)) {
+                    (CountType::Edge, _) | (_, None) => format!("#{}", i),
+                    (_, Some(node)) => node.clone(),
+                })
+                .collect();
+            warn_or_fail(graph_aux.strict, &format!(
+                "coverage of {} {}s exceeds the number of groups ({}), which indicates that they are counted more than once: {}{}",
+                items.len(),
+                count,
+                n,
+                names.join(", "),
+                if items.len() > names.len() { ", ..." } else { "" }
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+fn load_hist(hist_file: &str) -> Result<Hist, std::io::Error> {
+    log::info!("loading coverage histogram from {}", hist_file);
+    let mut data = buffered(fs::File::open(hist_file)?);
+    Hist::from_tsv(&mut data, None)
+}
+
+// builds the histogram from the abacus or loads it from file, depending on the command
+fn build_hist(
+    params: &Params,
+    abacus: &Abacus,
+    stats: &mut RunStats,
+) -> Result<Option<Hist>, std::io::Error> {
+    let mut hist: Option<Hist> = match params {
+        Params::Histgrowth { .. } | Params::Report { .. } | Params::Hist { .. } => {
+            if let Abacus::Total(abacus_total) = abacus {
                 // constructing histogram
                 log::info!("constructing histogram..");
                 Some(Hist::from_abacus(abacus_total))
@@ -613,106 +2099,1173 @@ pub fn run<W: Write>(params: Params, out: &mut BufWriter<W>) -> Result<(), std::
                 None
             }
         }
-        Params::Growth { hist_file, .. } => {
+        Params::Growth {
+            hist_file, count, ..
+        } => {
             log::info!("loading coverage histogram from {}", hist_file);
-            let mut data = std::io::BufReader::new(fs::File::open(&hist_file)?);
-            Some(Hist::from_tsv(&mut data)?)
+            let mut data = buffered(fs::File::open(&hist_file)?);
+            let hist = Hist::from_tsv(&mut data, *count)?;
+            log::info!("histogram reports counts of type \"{}\"", hist.count);
+            stats.add_str("count", &hist.count.to_string());
+            Some(hist)
         }
-        Params::OrderedHistgrowth { .. } | Params::Table { .. } => {
+        Params::OrderedHistgrowth { .. }
+        | Params::Hotspots { .. }
+        | Params::Table { .. }
+        | Params::Rarefy { .. }
+        | Params::Attribution { .. }
+        | Params::Item { .. }
+        | Params::Compare { .. }
+        | Params::EdgeAudit { .. }
+        | Params::NodeLengths { .. }
+        | Params::PrivateBed { .. }
+        | Params::Subgraph { .. }
+        | Params::Bench { .. }
+        | Params::HistMerge { .. }
+        | Params::AtCoverage { .. }
+        | Params::Estimate { .. } => {
             // do nothing
             None
         }
     };
+    if let (Params::Histgrowth { save_hist, .. }, Some(hist)) = (params, &hist) {
+        // the histogram is saved as computed, i.e., before private countables are excluded
+        if !save_hist.is_empty() {
+            log::info!("writing histogram to {}", save_hist);
+            let mut data = BufWriter::new(fs::File::create(save_hist)?);
+            writeln!(
+                data,
+                "# {}",
+                std::env::args().collect::<Vec<String>>().join(" ")
+            )?;
+            hist.to_tsv(false, 0, false, &mut data)?;
+            data.flush()?;
+        }
+    }
+    if let (
+        Params::Histgrowth {
+            exclude_private: true,
+            ..
+        }
+        | Params::Growth {
+            exclude_private: true,
+            ..
+        },
+        Some(hist),
+    ) = (params, hist.as_mut())
+    {
+        if hist.coverage.len() > 1 {
+            log::info!(
+                "excluding {} private {}s from histogram",
+                hist.coverage[1],
+                hist.count
+            );
+            hist.coverage[1] = 0;
+        }
+    }
+    Ok(hist)
+}
 
-    //
-    // 4th step: calculation & output of growth curve / output of histogram
-    //
-    //
+fn write_model_fit<W: Write>(hist: &Hist, out: &mut BufWriter<W>) -> Result<(), std::io::Error> {
+    let n = hist.coverage.len() - 1;
+    if n < 3 {
+        let msg = format!(
+            "fitting a model requires at least 3 groups, but histogram reports {}",
+            n
+        );
+        log::error!("{}", &msg);
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+    }
+    let (c, q) = GrowthCurve::Core.thresholds();
+    let core = hist.calc_growth(&c, &q);
+    log::info!("fitting tettelin-core model to core growth curve");
+    let (k, tau, omega, rss) = fit_tettelin_core(&core);
+    writeln!(out, "model\tk\ttau\tomega\trss")?;
     writeln!(
         out,
-        "# {}",
-        std::env::args().collect::<Vec<String>>().join(" ")
+        "tettelin-core\t{:.4}\t{:.4}\t{:.4}\t{:.4}",
+        k, tau, omega, rss
     )?;
+    Ok(())
+}
 
-    //    if let Abacus::Group(abacus_group) = &abacus {
-    //        abacus_group.write_rcv(out)?;
-    //        out.flush()?;
-    //        std::process::exit(0x0100);
-    //    }
-
-    match params {
-        Params::Histgrowth { .. } | Params::Growth { .. } | Params::OrderedHistgrowth { .. } => {
-            let hist_aux = HistAuxilliary::from_params(&params)?;
+// calculates the growth curves of the given columns, i.e., coverage/quorum pairs and, if
+// dual-count is set, count types, either from the histogram or the abacus of ordered growth
+fn calc_growths(
+    params: &Params,
+    hist: Option<&Hist>,
+    abacus: &Abacus,
+    columns: &[(Threshold, Threshold, Option<CountType>)],
+    denominator: Option<Denominator>,
+) -> Result<Vec<Vec<f64>>, std::io::Error> {
+    let item_sample = match (params, abacus) {
+        (
+            Params::OrderedHistgrowth {
+                item_subsample: Some(fraction),
+                abacus_args,
+                ..
+            },
+            Abacus::Group(abacus_group),
+        ) => Some(abacus_group.sample_items(*fraction, abacus_args.seed)?),
+        _ => None,
+    };
 
-            //let growths: Vec<Vec<usize>> = hist_aux
-            let growths: Vec<Vec<f64>> = hist_aux
-                .coverage
-                .par_iter()
-                .zip(&hist_aux.quorum)
-                .map(|(c, q)| {
-                    match params {
-                        Params::OrderedHistgrowth { .. } => {
-                            if let Abacus::Group(abacus_group) = &abacus {
-                                log::info!("calculating ordered growth for coverage >= {} and quorum >= {}", &c, &q);
-                                abacus_group.calc_growth(&c, &q)
-                            } else {
-                                unreachable!()
-                            }
-                        }
-                        _ => {
-                            log::info!("calculating growth for coverage >= {} and quorum >= {}", &c, &q);
-                            // <hist> must be some-thing in histgrowth and growth, so let's unwrap it!
-                            hist.as_ref().unwrap().calc_growth(&c, &q)
-                        }
+    //let growths: Vec<Vec<usize>> = hist_aux
+    let mut growths: Vec<Vec<f64>> = columns
+        .par_iter()
+        .map(|(c, q, count)| {
+            match params {
+                Params::OrderedHistgrowth {
+                    direction,
+                    stepwise_thresholds,
+                    ..
+                } => {
+                    if let Abacus::Group(abacus_group) = abacus {
+                        let count = count.unwrap_or(abacus_group.count);
+                        log::info!("calculating ordered {} of {} count for coverage >= {} and quorum >= {}", if *direction == Direction::Decay { "decay" } else { "growth" }, count, &c, &q);
+                        abacus_group.calc_growth(
+                            &c,
+                            &q,
+                            count,
+                            item_sample.as_deref(),
+                            *direction,
+                            *stepwise_thresholds,
+                        )
+                    } else {
+                        unreachable!()
                     }
-                })
-                .collect();
+                }
+                _ => {
+                    log::info!("calculating growth for coverage >= {} and quorum >= {}", &c, &q);
+                    // <hist> must be some-thing in histgrowth and growth, so let's unwrap it!
+                    hist.unwrap().calc_growth(&c, &q)
+                }
+            }
+        })
+        .collect();
+
+    if let (
+        Params::OrderedHistgrowth {
+            baseline: Some(baseline),
+            ..
+        },
+        Abacus::Group(abacus_group),
+    ) = (params, abacus)
+    {
+        for (growth, (_, _, count)) in growths.iter_mut().zip(columns) {
+            let count = count.unwrap_or(abacus_group.count);
+            let b = abacus_group.group_size(baseline, count)?;
+            log::info!(
+                "subtracting size {} of baseline group {} from {} growth",
+                b,
+                baseline,
+                count
+            );
+            growth.iter_mut().for_each(|x| *x = f64::max(0.0, *x - b));
+        }
+    }
+
+    if let Some(denominator) = denominator {
+        for (growth, (_, _, count)) in growths.iter_mut().zip(columns) {
+            let (count, total) = match (abacus, denominator) {
+                (Abacus::Total(abacus_total), Denominator::Paths) => (
+                    abacus_total.count,
+                    hist.unwrap().coverage[1..].iter().sum::<usize>() as f64,
+                ),
+                (Abacus::Total(abacus_total), Denominator::Graph) => (
+                    abacus_total.count,
+                    abacus_total.graph_aux.graph_size(abacus_total.count),
+                ),
+                (Abacus::Group(abacus_group), Denominator::Paths) => {
+                    let count = count.unwrap_or(abacus_group.count);
+                    (count, abacus_group.covered_size(count))
+                }
+                (Abacus::Group(abacus_group), Denominator::Graph) => {
+                    let count = count.unwrap_or(abacus_group.count);
+                    (count, abacus_group.graph_aux.graph_size(count))
+                }
+                (Abacus::Nil, _) => unreachable!(),
+            };
+            log::info!("reporting growth relative to {} {}s in total", total, count);
+            growth
+                .iter_mut()
+                .for_each(|x| *x = if total > 0.0 { *x / total } else { 0.0 });
+        }
+    }
+    Ok(growths)
+}
 
-            // number of groups
-            let n = growths[0].len();
+// output of histgrowth, growth, and ordered-histgrowth
+fn write_growth<W: Write>(
+    params: &Params,
+    hist: Option<&Hist>,
+    hist_aux: &HistAuxilliary,
+    abacus: &mut Abacus,
+    order_weights: Option<HashMap<String, f64>>,
+    html: bool,
+    strict: bool,
+    out: &mut BufWriter<W>,
+) -> Result<(), std::io::Error> {
+    let GrowthArgs {
+        long,
+        sql_friendly,
+        efficiency,
+        round,
+        precision,
+        ..
+    } = params.growth_args().unwrap().clone();
+    let long = long || sql_friendly;
 
-            writeln!(
-                out,
-                "coverage\t{}",
+    let n_groups = match &abacus {
+        Abacus::Group(abacus_group) => abacus_group.groups.len(),
+        _ => hist.unwrap().coverage.len() - 1,
+    };
+    if n_groups == 1
+        && hist_aux
+            .quorum
+            .iter()
+            .any(|q| matches!(q, Threshold::Relative(x) if *x > 0.0))
+    {
+        warn_or_fail(strict, "relative quorum thresholds are given, but data has only a single group; quorum is trivially satisfied and the growth curve will be identical for all quorum values")?;
+    }
+
+    let denominator = match params {
+        Params::Histgrowth { denominator, .. } | Params::OrderedHistgrowth { denominator, .. } => {
+            *denominator
+        }
+        _ => None,
+    };
+    // fractions must not be rounded to integers
+    let round = if denominator.is_some() {
+        Rounding::None
+    } else {
+        round
+    };
+    // each column of the output corresponds to a coverage/quorum pair and, if dual-count
+    // is set, to one of the given count types
+    let dual_count = match params {
+        Params::OrderedHistgrowth { dual_count, .. } => dual_count.clone(),
+        _ => Vec::new(),
+    };
+    let columns: Vec<(Threshold, Threshold, Option<CountType>)> = if dual_count.is_empty() {
+        hist_aux
+            .coverage
+            .iter()
+            .zip(&hist_aux.quorum)
+            .map(|(c, q)| (*c, *q, None))
+            .collect()
+    } else {
+        dual_count
+            .iter()
+            .flat_map(|t| {
                 hist_aux
                     .coverage
                     .iter()
-                    .map(|x| x.to_string())
+                    .zip(&hist_aux.quorum)
+                    .map(move |(c, q)| (*c, *q, Some(*t)))
+            })
+            .collect()
+    };
+    // a single growth curve computed from the histogram is written while it is being
+    // calculated. Multiple curves and ordered growth are not streamed: each row holds a value of
+    // every curve, and sorting groups by contribution, decay, and the HTML report need random
+    // access to the rows, so all curves are computed before the first row is written
+    let stream = !html && hist.is_some() && columns.len() == 1 && denominator.is_none();
+
+    let growths: Vec<Vec<f64>> = if stream {
+        Vec::new()
+    } else {
+        calc_growths(params, hist, abacus, &columns, denominator)?
+    };
+
+    // the row of a group in a decay curve covers the group and all groups that follow it, so that
+    // its predecessor step is the row of the next group
+    let decay = matches!(
+        params,
+        Params::OrderedHistgrowth {
+            direction: Direction::Decay,
+            ..
+        }
+    );
+    let prev = |g: &[f64], i: usize| -> f64 {
+        if decay {
+            g.get(i + 1).copied().unwrap_or(0.0)
+        } else if i > 0 {
+            g[i - 1]
+        } else {
+            0.0
+        }
+    };
+
+    // x-axis of weighted ordered growth, determined before groups are possibly renamed
+    let cumulative_weights: Option<Vec<f64>> = match (&order_weights, &*abacus) {
+        (Some(w), Abacus::Group(abacus_group)) => {
+            let weights: Vec<f64> = abacus_group
+                .groups
+                .iter()
+                .map(|g| w.get(g).copied().unwrap_or(1.0))
+                .collect();
+            let cumulate = |acc: &mut f64, x: &f64| {
+                *acc += x;
+                Some(*acc)
+            };
+            if decay {
+                let mut cw: Vec<f64> = weights.iter().rev().scan(0.0, cumulate).collect();
+                cw.reverse();
+                Some(cw)
+            } else {
+                Some(weights.iter().scan(0.0, cumulate).collect())
+            }
+        }
+        _ => None,
+    };
+
+    if let (Params::OrderedHistgrowth { group_rename, .. }, Abacus::Group(abacus_group)) =
+        (params, &mut *abacus)
+    {
+        if !group_rename.is_empty() {
+            abacus_group.rename_groups(group_rename)?;
+        }
+    }
+
+    if let (
+        Params::OrderedHistgrowth {
+            audit_contributions,
+            ..
+        },
+        Abacus::Group(abacus_group),
+    ) = (params, &*abacus)
+    {
+        if !audit_contributions.is_empty() {
+            log::info!(
+                "writing contributions of each group to {}",
+                audit_contributions
+            );
+            let mut audit = BufWriter::new(fs::File::create(audit_contributions)?);
+            abacus_group.contributions_to_tsv(decay, &mut audit)?;
+            audit.flush()?;
+        }
+    }
+
+    // order in which the rows (i.e., groups or numbers of groups) are reported
+    let mut rows: Vec<usize> = (0..growths.get(0).map(|g| g.len()).unwrap_or(0)).collect();
+    if let Params::OrderedHistgrowth {
+        sort_groups_by_contribution: true,
+        ..
+    } = params
+    {
+        log::info!("sorting groups by their marginal contribution");
+        let g = &growths[0];
+        let contribution = |i: usize| g[i] - prev(g, i);
+        // stable sort, so groups of equal contribution remain in order
+        rows.sort_by(|a, b| contribution(*b).total_cmp(&contribution(*a)));
+    }
+
+    if html {
+        if let Abacus::Total(abacus_total) = &*abacus {
+            log::info!("reporting histogram and growth as HTML");
+            html::write_histgrowth_html(
+                hist.unwrap(),
+                hist_aux,
+                &growths,
+                &abacus_total.graph_aux,
+                n_groups,
+                round,
+                precision,
+                out,
+            )?;
+        }
+        return Ok(());
+    }
+
+    if long {
+        // tidy format: one row per group and coverage/quorum pair
+        writeln!(
+            out,
+            "{}{}\tvalue{}",
+            match (sql_friendly, cumulative_weights.is_some()) {
+                (true, false) => "step\tcoverage\tquorum\tgroup",
+                (true, true) => "step\tcoverage\tquorum\tgroup\tweight",
+                (false, false) => "group\tcoverage\tquorum",
+                (false, true) => "group\tweight\tcoverage\tquorum",
+            },
+            if dual_count.is_empty() { "" } else { "\tcount" },
+            if efficiency { "\tefficiency" } else { "" }
+        )?;
+    } else {
+        // efficiency columns follow the growth columns in the same order
+        let k = if efficiency { 2 } else { 1 };
+        // the column of cumulative weights, if any, has no coverage/quorum
+        let w = if cumulative_weights.is_some() {
+            "\t"
+        } else {
+            ""
+        };
+        writeln!(
+            out,
+            "coverage\t{}{}",
+            w,
+            vec![
+                columns
+                    .iter()
+                    .map(|(c, _, _)| c.to_string())
                     .collect::<Vec<String>>()
-                    .join("\t")
+                    .join("\t");
+                k
+            ]
+            .join("\t")
+        )?;
+        writeln!(
+            out,
+            "quorum\t{}{}",
+            w,
+            vec![
+                columns
+                    .iter()
+                    .map(|(_, q, _)| q.to_string())
+                    .collect::<Vec<String>>()
+                    .join("\t");
+                k
+            ]
+            .join("\t")
+        )?;
+        if !dual_count.is_empty() {
+            writeln!(
+                out,
+                "count\t{}{}",
+                w,
+                vec![
+                    columns
+                        .iter()
+                        .map(|(_, _, t)| t.unwrap().to_string())
+                        .collect::<Vec<String>>()
+                        .join("\t");
+                    k
+                ]
+                .join("\t")
             )?;
+        }
+        if efficiency || cumulative_weights.is_some() {
+            let n = columns.len();
             writeln!(
                 out,
-                "quorum\t{}",
-                hist_aux
-                    .quorum
-                    .iter()
-                    .map(|x| x.to_string())
-                    .collect::<Vec<String>>()
-                    .join("\t")
+                "measure\t{}{}{}",
+                if cumulative_weights.is_some() {
+                    "weight\t"
+                } else {
+                    ""
+                },
+                vec!["growth"; n].join("\t"),
+                if efficiency {
+                    format!("\t{}", vec!["efficiency"; n].join("\t"))
+                } else {
+                    String::new()
+                }
             )?;
-            for i in 0..n {
-                if let Abacus::Group(abacus_group) = &abacus {
-                    write!(out, "{}", &abacus_group.groups[i][..])?;
+        }
+    }
+
+    if stream {
+        let (c, q) = (&columns[0].0, &columns[0].1);
+        log::info!(
+            "calculating growth for coverage >= {} and quorum >= {}",
+            c,
+            q
+        );
+        let prefix = if sql_friendly {
+            format!("\t{}\t{}\t", c.to_string(), q.to_string())
+        } else if long {
+            format!("\t{}\t{}", c.to_string(), q.to_string())
+        } else {
+            String::new()
+        };
+        let mut i = 0;
+        let mut prev = 0.0;
+        let mut res = Ok(());
+        hist.unwrap().calc_growth_with(c, q, |y| {
+            i += 1;
+            if res.is_ok() {
+                res = if efficiency {
+                    writeln!(
+                        out,
+                        "{}{}\t{}\t{:.4}",
+                        i,
+                        &prefix,
+                        round.format(y, precision),
+                        growth_efficiency(prev, y)
+                    )
                 } else {
-                    write!(out, "{}", i + 1)?;
+                    writeln!(out, "{}{}\t{}", i, &prefix, round.format(y, precision))
+                };
+            }
+            prev = y;
+        });
+        res?;
+    } else if long {
+        for &i in rows.iter() {
+            let group = if let Abacus::Group(abacus_group) = &*abacus {
+                abacus_group.groups[i].clone()
+            } else if sql_friendly {
+                String::new()
+            } else {
+                (i + 1).to_string()
+            };
+            let weight = match &cumulative_weights {
+                Some(cw) => format!("\t{}", cw[i]),
+                None => String::new(),
+            };
+            for j in 0..columns.len() {
+                if sql_friendly {
+                    write!(
+                        out,
+                        "{}\t{}\t{}\t{}{}",
+                        i + 1,
+                        columns[j].0.to_string(),
+                        columns[j].1.to_string(),
+                        &group,
+                        &weight,
+                    )?;
+                } else {
+                    write!(
+                        out,
+                        "{}{}\t{}\t{}",
+                        &group,
+                        &weight,
+                        columns[j].0.to_string(),
+                        columns[j].1.to_string(),
+                    )?;
+                }
+                if let Some(t) = columns[j].2 {
+                    write!(out, "\t{}", t)?;
                 }
-                for j in 0..hist_aux.quorum.len() {
-                    write!(out, "\t{:0}", growths[j][i].floor())?;
+                write!(out, "\t{}", round.format(growths[j][i], precision))?;
+                if efficiency {
+                    write!(
+                        out,
+                        "\t{:.4}",
+                        growth_efficiency(prev(&growths[j], i), growths[j][i])
+                    )?;
                 }
                 writeln!(out, "")?;
             }
         }
-        Params::Hist { count, .. } => {
-            hist.unwrap().to_tsv(&count, out)?;
-        }
-        Params::Table { total, .. } => {
-            if let Abacus::Group(abacus_group) = abacus {
-                log::info!("reporting coverage table");
-                abacus_group.to_tsv(total, out)?;
+    } else {
+        for &i in rows.iter() {
+            if let Abacus::Group(abacus_group) = &*abacus {
+                write!(out, "{}", &abacus_group.groups[i][..])?;
+            } else {
+                write!(out, "{}", i + 1)?;
+            }
+            if let Some(cw) = &cumulative_weights {
+                write!(out, "\t{}", cw[i])?;
             }
+            for j in 0..columns.len() {
+                write!(out, "\t{}", round.format(growths[j][i], precision))?;
+            }
+            if efficiency {
+                for j in 0..columns.len() {
+                    write!(
+                        out,
+                        "\t{:.4}",
+                        growth_efficiency(prev(&growths[j], i), growths[j][i])
+                    )?;
+                }
+            }
+            writeln!(out, "")?;
         }
+    }
+    Ok(())
+}
+
+fn run_edge_audit<W: Write>(
+    graph_args: &GraphArgs,
+    strict: bool,
+    out: &mut BufWriter<W>,
+) -> Result<(), std::io::Error> {
+    log::info!(
+        "indexing edges of L-lines and paths/walks in {}",
+        &graph_args.gfa_file
+    );
+    let mut data = graph_args.open()?;
+    let graph_aux = graph_args.parse_args.index_graph(
+        &mut data,
+        IndexOptions {
+            index_edges: true,
+            strict,
+            ..Default::default()
+        },
+    )?;
+    log::info!("reporting edge audit");
+    graph_aux.write_edge_audit(out)
+}
+
+fn run_private_bed<W: Write>(
+    abacus: &AbacusByTotal,
+    graph_args: &GraphArgs,
+    reference: &str,
+    out: &mut BufWriter<W>,
+) -> Result<(), std::io::Error> {
+    let graph_aux = &abacus.graph_aux;
+    let reference = PathSegment::from_str(reference).clear_coords();
+    if !graph_aux
+        .path_segments
+        .iter()
+        .any(|x| x.clear_coords() == reference)
+    {
+        let msg = format!("reference path {} not found in graph", &reference);
+        log::error!("{}", &msg);
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+    }
+    // positions along the reference are tracked on its nodes, whose coverage tells whether they
+    // are private
+    log::info!("collecting nodes traversed by paths/walks");
+    let mut data = graph_args.open()?;
+    let path_items = io::parse_gfa_path_items(&mut data, graph_aux, CountType::Node)?;
+    log::info!("reporting private intervals of {}", &reference);
+    graph_aux.write_private_bed(&path_items, &abacus.countable, &reference, out)
+}
+
+fn run_subgraph<W: Write>(
+    abacus: &AbacusByTotal,
+    graph_args: &GraphArgs,
+    min_coverage: usize,
+    output: &str,
+    out: &mut BufWriter<W>,
+) -> Result<(), std::io::Error> {
+    let graph_aux = &abacus.graph_aux;
+    let keep: Vec<bool> = abacus
+        .countable
+        .iter()
+        .map(|&x| x as usize >= min_coverage)
+        .collect();
+    log::info!(
+        "retaining {} of {} nodes that are traversed by at least {} groups",
+        keep.iter().skip(1).filter(|&&x| x).count(),
+        graph_aux.number_of_nodes(),
+        min_coverage
+    );
+    let mut data = graph_args.open()?;
+    if output.is_empty() {
+        io::write_subgraph(&mut data, graph_aux, &keep, out)?;
+    } else {
+        log::info!("writing subgraph to {}", output);
+        let mut sub = BufWriter::new(fs::File::create(output)?);
+        io::write_subgraph(&mut data, graph_aux, &keep, &mut sub)?;
+        sub.flush()?;
+    }
+    Ok(())
+}
+
+fn run_node_lengths<W: Write>(
+    graph_args: &GraphArgs,
+    bin_width: usize,
+    log_bins: bool,
+    ignore_softmask: bool,
+    strict: bool,
+    out: &mut BufWriter<W>,
+) -> Result<(), std::io::Error> {
+    log::info!("indexing nodes of {}", &graph_args.gfa_file);
+    let mut data = graph_args.open()?;
+    let graph_aux = graph_args.parse_args.index_graph(
+        &mut data,
+        IndexOptions {
+            ignore_softmask,
+            strict,
+            ..Default::default()
+        },
+    )?;
+    log::info!("reporting node length distribution");
+    graph_aux.write_node_len_hist(bin_width, log_bins, out)
+}
+
+fn run_bench<W: Write>(
+    nodes: usize,
+    paths: usize,
+    seed: u64,
+    shards: Option<usize>,
+    parse_args: &ParseArgs,
+    strict: bool,
+    out: &mut BufWriter<W>,
+) -> Result<(), std::io::Error> {
+    // each phase is reported along with the peak memory at its end
+    let mut phases: Vec<(&str, f64, Option<usize>)> = Vec::new();
+    let timer = Instant::now();
+    log::info!(
+        "generating synthetic graph with {} nodes and {} paths",
+        nodes,
+        paths
+    );
+    let gfa = io::synthetic_gfa(nodes, paths, seed);
+    phases.push(("generate", timer.elapsed().as_secs_f64(), peak_memory_kb()));
+
+    let timer = Instant::now();
+    let graph_aux = parse_args.index_graph(
+        &mut buffered(&gfa[..]),
+        IndexOptions {
+            strict,
+            ..Default::default()
+        },
+    )?;
+    let abacus_aux = AbacusAuxilliary {
+        count: CountType::Node,
+        groups: AbacusAuxilliary::load_groups("", false, false, false, &graph_aux)?,
+        include_coords: None,
+        exclude_coords: None,
+        order: None,
+        order_weights: None,
+        depth_range: None,
+        reference: None,
+        reference_coords: None,
+        relative_to_reference: false,
+        multiplicity: false,
+        shards: shards,
     };
+    let link_count = graph_aux.link_count;
+    phases.push(("index", timer.elapsed().as_secs_f64(), peak_memory_kb()));
+
+    let timer = Instant::now();
+    let abacus = AbacusByTotal::from_gfa(
+        &mut buffered(&gfa[..]),
+        abacus_aux.clone(),
+        graph_aux.clone(),
+    )?;
+    phases.push(("count", timer.elapsed().as_secs_f64(), peak_memory_kb()));
+
+    let timer = Instant::now();
+    let hist = Hist::from_abacus(&abacus);
+    phases.push(("hist", timer.elapsed().as_secs_f64(), peak_memory_kb()));
+
+    let timer = Instant::now();
+    let growth = hist.calc_growth(&Threshold::Absolute(1), &Threshold::Relative(0.0));
+    phases.push(("growth", timer.elapsed().as_secs_f64(), peak_memory_kb()));
+    log::info!(
+        "pangenome of {} paths comprises {} nodes",
+        paths,
+        growth.last().copied().unwrap_or(0.0)
+    );
+
+    // the ordered growth is computed from the per-group abacus, whose computation is parallelized
+    // across items rather than across coverage/quorum thresholds
+    let timer = Instant::now();
+    let abacus_group =
+        AbacusByGroup::from_gfa(&mut buffered(&gfa[..]), abacus_aux, graph_aux, false, false)?;
+    phases.push((
+        "count_groups",
+        timer.elapsed().as_secs_f64(),
+        peak_memory_kb(),
+    ));
+
+    let timer = Instant::now();
+    abacus_group.calc_growth(
+        &Threshold::Absolute(1),
+        &Threshold::Relative(0.0),
+        CountType::Node,
+        None,
+        Direction::Grow,
+        false,
+    );
+    phases.push((
+        "ordered_growth",
+        timer.elapsed().as_secs_f64(),
+        peak_memory_kb(),
+    ));
+
+    writeln!(
+        out,
+        "# synthetic graph with {} nodes, {} L-lines, and {} paths ({} bytes), item table with {} shards, {} threads",
+        nodes,
+        link_count,
+        paths,
+        gfa.len(),
+        shards.unwrap_or_else(|| ItemTable::shards(nodes)),
+        rayon::current_num_threads()
+    )?;
+    writeln!(out, "phase\tseconds\tpeak_memory_kb")?;
+    for (phase, secs, mem) in phases {
+        writeln!(
+            out,
+            "{}\t{:.4}\t{}",
+            phase,
+            secs,
+            mem.map(|x| x.to_string())
+                .unwrap_or_else(|| "NA".to_string())
+        )?;
+    }
+    Ok(())
+}
+
+fn run_hist_merge<W: Write>(
+    hist_files: &[String],
+    names: &[String],
+    out: &mut BufWriter<W>,
+) -> Result<(), std::io::Error> {
+    if !names.is_empty() && names.len() != hist_files.len() {
+        let msg = format!(
+            "number of names ({}) must match that of histograms ({})",
+            names.len(),
+            hist_files.len()
+        );
+        log::error!("{}", &msg);
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+    }
+    let mut hists = Vec::new();
+    for f in hist_files.iter() {
+        hists.push(load_hist(f)?);
+    }
+    let names = if names.is_empty() { hist_files } else { names };
+    log::info!("reporting merged histograms");
+    Hist::merge_to_tsv(&hists, names, out)
+}
+
+fn run_rarefy<W: Write>(
+    abacus_group: &AbacusByGroup,
+    size: usize,
+    replicates: usize,
+    seed: Option<u64>,
+    out: &mut BufWriter<W>,
+) -> Result<(), std::io::Error> {
+    let n = abacus_group.groups.len();
+    if size == 0 || size > n {
+        let msg = format!(
+            "rarefaction size must be between 1 and the number of groups ({}), but is {}",
+            n, size
+        );
+        log::error!("{}", &msg);
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+    }
+    if replicates == 0 {
+        let msg = "number of replicates must be at least 1";
+        log::error!("{}", &msg);
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+    }
+    log::info!(
+        "sampling {} groups out of {} in {} replicates",
+        size,
+        n,
+        replicates
+    );
+    let values = abacus_group.rarefy(size, replicates, seed);
+    let (mean, variance) = mean_variance(&values);
+    log::info!("reporting rarefaction");
+    writeln!(out, "count\tsize\treplicates\tmean\tvariance")?;
+    writeln!(
+        out,
+        "{}\t{}\t{}\t{:.4}\t{:.4}",
+        abacus_group.count, size, replicates, mean, variance
+    )?;
+    Ok(())
+}
 
+fn run_bootstrap<W: Write>(
+    abacus_group: &AbacusByGroup,
+    hist_aux: &HistAuxilliary,
+    replicates: usize,
+    seed: Option<u64>,
+    stepwise: bool,
+    out: &mut BufWriter<W>,
+) -> Result<(), std::io::Error> {
+    if replicates == 0 {
+        let msg = "number of bootstrap samples must be at least 1";
+        log::error!("{}", &msg);
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+    }
+    let n = abacus_group.groups.len();
+    log::info!(
+        "drawing {} bootstrap samples of {} groups with replacement",
+        replicates,
+        n
+    );
+    // all coverage/quorum pairs are evaluated on the same samples
+    let orders = abacus_group.bootstrap_orders(replicates, seed);
+    // per coverage/quorum pair, the mean and variance of each step
+    let stats: Vec<Vec<(f64, f64)>> = hist_aux
+        .coverage
+        .iter()
+        .zip(&hist_aux.quorum)
+        .map(|(c, q)| {
+            log::info!(
+                "calculating bootstrap growth for coverage >= {} and quorum >= {}",
+                c,
+                q
+            );
+            let growths: Vec<Vec<f64>> = orders
+                .iter()
+                .map(|o| abacus_group.calc_growth_in_order(o, c, q, abacus_group.count, stepwise))
+                .collect();
+            (0..n)
+                .map(|j| mean_variance(&growths.iter().map(|g| g[j]).collect::<Vec<f64>>()))
+                .collect()
+        })
+        .collect();
+
+    log::info!("reporting bootstrap growth");
+    for (label, values) in [
+        ("coverage", &hist_aux.coverage),
+        ("quorum", &hist_aux.quorum),
+    ] {
+        writeln!(
+            out,
+            "{}\t{}",
+            label,
+            values
+                .iter()
+                .map(|x| format!("{}\t{}", x.to_string(), x.to_string()))
+                .collect::<Vec<String>>()
+                .join("\t")
+        )?;
+    }
+    writeln!(
+        out,
+        "measure\t{}",
+        vec!["mean\tvariance"; stats.len()].join("\t")
+    )?;
+    for j in 0..n {
+        writeln!(
+            out,
+            "{}\t{}",
+            j + 1,
+            stats
+                .iter()
+                .map(|s| format!("{:.4}\t{:.4}", s[j].0, s[j].1))
+                .collect::<Vec<String>>()
+                .join("\t")
+        )?;
+    }
     Ok(())
 }
+
+fn run_item<W: Write>(
+    abacus_group: &AbacusByGroup,
+    id: &str,
+    out: &mut BufWriter<W>,
+) -> Result<(), std::io::Error> {
+    let graph_aux = &abacus_group.graph_aux;
+    let item = if abacus_group.count == CountType::Edge {
+        graph_aux.edge_id(id)
+    } else {
+        graph_aux.node_id(id.as_bytes()).copied()
+    };
+    match item {
+        Some(item) => {
+            log::info!("reporting coverage of {} {}", abacus_group.count, id);
+            abacus_group.item_to_tsv(id, item.0, out)
+        }
+        None => {
+            let msg = format!("{} {} not found in graph", abacus_group.count, id);
+            log::error!("{}", &msg);
+            Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_command_definition() {
+        Command::command().debug_assert();
+    }
+
+    #[test]
+    fn test_shared_options() {
+        for cmd in [
+            "histgrowth",
+            "hist",
+            "ordered-histgrowth",
+            "table",
+            "attribution",
+            "hotspots",
+        ] {
+            let params = Command::try_parse_from([
+                "panacus",
+                cmd,
+                "-H",
+                "--strip-prefix",
+                "x",
+                "-t",
+                "2",
+                "--subsample",
+                "0.5",
+                "g.gfa",
+            ])
+            .unwrap()
+            .cmd;
+            let args = params.abacus_args().unwrap();
+            assert_eq!(args.graph_args.gfa_file, "g.gfa");
+            assert_eq!(args.graph_args.parse_args.strip_prefix, "x");
+            assert!(args.group_args.groupby_haplotype);
+            assert_eq!(args.subsample, Some(0.5));
+            assert_eq!(params.threads(), Some(2));
+            assert!(params.count().is_some());
+        }
+    }
+
+    #[test]
+    fn test_edge_index_only_for_edges() {
+        let gfa =
+            std::env::temp_dir().join(format!("panacus_edge_index_{}.gfa", std::process::id()));
+        fs::write(
+            &gfa,
+            "S\t1\tAAA\nS\t2\tCC\nL\t1\t+\t2\t+\t0M\nP\tA#1#c\t1+,2+\t*\n",
+        )
+        .unwrap();
+        let gfa_file = gfa.to_str().unwrap();
+        for (cmd, count, edges) in [
+            ("histgrowth", "node", false),
+            ("hist", "bp", false),
+            ("ordered-histgrowth", "node", false),
+            ("table", "node", false),
+            ("hist", "edge", true),
+            ("table", "allele", true),
+        ] {
+            let params = Command::try_parse_from(["panacus", cmd, "-c", count, gfa_file])
+                .unwrap()
+                .cmd;
+            let (graph_aux, _) = index_graph(
+                &params,
+                params.abacus_args().unwrap(),
+                params.count().unwrap(),
+                &[],
+                false,
+                &mut RunStats::new(),
+            )
+            .unwrap()
+            .unwrap();
+            assert_eq!(graph_aux.edge2id.is_some(), edges, "{} -c {}", cmd, count);
+            assert_eq!(
+                graph_aux.path_edges.is_some(),
+                edges,
+                "{} -c {}",
+                cmd,
+                count
+            );
+        }
+        fs::remove_file(&gfa).unwrap();
+    }
+
+    #[test]
+    fn test_multiplicity_strict_list_items() {
+        let tmp = std::env::temp_dir().join(format!("panacus_multiplicity_{}", std::process::id()));
+        let gfa = format!("{}.gfa", tmp.to_str().unwrap());
+        let prefix = tmp.to_str().unwrap();
+        // node 1 is traversed three times by the only group
+        fs::write(
+            &gfa,
+            "S\t1\tAAA\nS\t2\tCC\nL\t1\t+\t2\t+\t0M\nL\t2\t+\t1\t+\t0M\nP\tA#1#c\t1+,2+,1+,2+,1+\t*\n",
+        )
+        .unwrap();
+        let params = Command::try_parse_from([
+            "panacus",
+            "hist",
+            "--multiplicity",
+            "--list-items",
+            prefix,
+            &gfa,
+        ])
+        .unwrap()
+        .cmd;
+        let mut stats = RunStats::new();
+        let (graph_aux, abacus_aux) = index_graph(
+            &params,
+            params.abacus_args().unwrap(),
+            params.count().unwrap(),
+            &[],
+            true,
+            &mut stats,
+        )
+        .unwrap()
+        .unwrap();
+        let (abacus, _) = build_abacus(&params, graph_aux, abacus_aux, &[], &mut stats).unwrap();
+        // traversal counts exceeding the number of groups are not reported as overcounting, which
+        // would be an error in strict mode
+        check_abacus(&abacus).unwrap();
+        // items of any traversal count are listed
+        if let Abacus::Total(abacus_total) = &abacus {
+            abacus_total.write_item_lists(prefix).unwrap();
+        }
+        for (c, expected) in [(1, ""), (2, "2\n"), (3, "1\n")] {
+            let file_name = format!("{}.{}.txt", prefix, c);
+            assert_eq!(fs::read_to_string(&file_name).unwrap(), expected);
+            fs::remove_file(&file_name).unwrap();
+        }
+        fs::remove_file(format!("{}.0.txt", prefix)).unwrap();
+        fs::remove_file(&gfa).unwrap();
+    }
+
+    #[test]
+    fn test_coverage_commands_use_abacus_args() {
+        let tmp = std::env::temp_dir().join(format!("panacus_subset_{}", std::process::id()));
+        let gfa = format!("{}.gfa", tmp.to_str().unwrap());
+        let subset = format!("{}.txt", tmp.to_str().unwrap());
+        fs::write(
+            &gfa,
+            "S\t1\tAAA\nS\t2\tCC\nS\t3\tG\nL\t1\t+\t2\t+\t0M\nL\t2\t+\t3\t+\t0M\nP\tA#1#c\t1+,2+\t*\nP\tB#1#c\t2+,3+\t*\n",
+        )
+        .unwrap();
+        fs::write(&subset, "A#1#c\n").unwrap();
+        let abacus = |args: &[&str]| {
+            let params = Command::try_parse_from(args).unwrap().cmd;
+            let mut stats = RunStats::new();
+            let (graph_aux, abacus_aux) = index_graph(
+                &params,
+                params.abacus_args().unwrap(),
+                params.count().unwrap(),
+                &[],
+                false,
+                &mut stats,
+            )
+            .unwrap()
+            .unwrap();
+            match build_abacus(&params, graph_aux, abacus_aux, &[], &mut stats).unwrap() {
+                (Abacus::Total(abacus_total), _) => (params, abacus_total),
+                _ => unreachable!(),
+            }
+        };
+
+        // only nodes of the subset paths are retained
+        let (params, abacus_total) =
+            abacus(&["panacus", "subgraph", "-c", "1", "-s", &subset, &gfa]);
+        let mut out = BufWriter::new(Vec::new());
+        run_subgraph(&abacus_total, params.graph_args().unwrap(), 1, "", &mut out).unwrap();
+        let sub = String::from_utf8(out.into_inner().unwrap()).unwrap();
+        assert!(sub.contains("S\t1\t") && sub.contains("S\t2\t") && !sub.contains("S\t3\t"));
+
+        // node 2 is shared with B, unless B is excluded by the subset
+        for (args, expected) in [
+            (
+                vec!["panacus", "private-bed", "--ref", "A#1#c", &gfa],
+                "A#1#c\t0\t3\n",
+            ),
+            (
+                vec![
+                    "panacus",
+                    "private-bed",
+                    "--ref",
+                    "A#1#c",
+                    "-s",
+                    &subset,
+                    &gfa,
+                ],
+                "A#1#c\t0\t5\n",
+            ),
+        ] {
+            let (params, abacus_total) = abacus(&args);
+            let mut out = BufWriter::new(Vec::new());
+            run_private_bed(
+                &abacus_total,
+                params.graph_args().unwrap(),
+                "A#1#c",
+                &mut out,
+            )
+            .unwrap();
+            assert_eq!(
+                String::from_utf8(out.into_inner().unwrap()).unwrap(),
+                expected
+            );
+        }
+        fs::remove_file(&gfa).unwrap();
+        fs::remove_file(&subset).unwrap();
+    }
+}
@@ -1,11 +1,16 @@
 /* standard use */
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::io::{BufWriter, Write};
 use std::str::{self, FromStr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 
 /* private use */
 use crate::io;
-use crate::util::{CountType, ItemIdSize};
+use crate::util::{
+    normalize_id, percent_decode, warn_or_fail, CountSize, CountType, ItemIdSize, MissingLength,
+};
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ItemId(pub ItemIdSize);
@@ -20,17 +25,15 @@ impl fmt::Display for ItemId {
 pub struct Edge(pub ItemId, pub Orientation, pub ItemId, pub Orientation);
 
 impl Edge {
-    pub fn from_link(data: &[u8], node2id: &HashMap<Vec<u8>, ItemId>, canonical: bool) -> Self {
+    // splits an L-line (or its part from the 2nd column on) into node names and orientations
+    fn parse_link(data: &[u8]) -> (&[u8], Orientation, &[u8], Orientation) {
         let (start, mut iter) = match data[0] {
             b'L' => (2, data[2..].iter()),
             _ => (0, data.iter()),
         };
 
         let end = start + iter.position(|&x| x == b'\t').unwrap();
-        let u = node2id.get(&data[start..end]).expect(&format!(
-            "unknown node {}",
-            str::from_utf8(&data[start..end]).unwrap()
-        ));
+        let u = &data[start..end];
 
         // we know that 3rd colum is either '+' or '-', so it has always length 1; still, we
         // need to advance in the buffer (and  therefore call iter.position(..))
@@ -39,17 +42,49 @@ impl Edge {
 
         let start = end + 3;
         let end = start + iter.position(|&x| x == b'\t').unwrap();
-
-        let v = node2id.get(&data[start..end]).expect(&format!(
-            "unknown node {}",
-            str::from_utf8(&data[start..end]).unwrap()
-        ));
+        let v = &data[start..end];
         let o2 = Orientation::from_pm(data[end + 1]);
+        (u, o1, v, o2)
+    }
+
+    // name of the link in the notation of edge_name, e.g., >1<2, irrespective of whether its
+    // nodes are known
+    pub fn link_name(data: &[u8]) -> String {
+        let (u, o1, v, o2) = Self::parse_link(data);
+        format!(
+            "{}{}{}{}",
+            o1,
+            str::from_utf8(u).unwrap(),
+            o2,
+            str::from_utf8(v).unwrap()
+        )
+    }
+
+    pub fn from_link(
+        data: &[u8],
+        graph_aux: &GraphAuxilliary,
+        canonical: bool,
+    ) -> Result<Self, std::io::Error> {
+        let (u_name, o1, v_name, o2) = Self::parse_link(data);
+        let node_id = |node: &[u8]| {
+            graph_aux.node_id(node).copied().ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "L-line {} refers to undefined segment {}",
+                        Self::link_name(data),
+                        str::from_utf8(node).unwrap()
+                    ),
+                )
+            })
+        };
+        let u = node_id(u_name)?;
+        let v = node_id(v_name)?;
 
         if canonical {
-            Self::canonical(*u, o1, *v, o2)
+            Ok(Self::canonical(u, o1, v, o2))
         } else {
-            Self(*u, o1, *v, o2)
+            Ok(Self(u, o1, v, o2))
         }
     }
 
@@ -142,14 +177,69 @@ impl PartialEq<u8> for Orientation {
     }
 }
 
+// settings that determine how a graph is indexed
+#[derive(Debug, Clone)]
+pub struct IndexOptions {
+    // whether L-lines and edges traversed by paths/walks are indexed
+    pub index_edges: bool,
+    // prefix that is stripped from node IDs
+    pub strip_prefix: String,
+    // whether only upper-case bases are counted as segment length
+    pub ignore_softmask: bool,
+    // S-line tag that gives the per-node depth, if any
+    pub depth_tag: String,
+    pub decode_names: bool,
+    pub normalize_ids: bool,
+    // how segments of unknown length are treated
+    pub missing_length: MissingLength,
+    pub strict: bool,
+}
+
+impl Default for IndexOptions {
+    fn default() -> Self {
+        Self {
+            index_edges: false,
+            strip_prefix: String::new(),
+            ignore_softmask: false,
+            depth_tag: String::new(),
+            decode_names: false,
+            normalize_ids: false,
+            missing_length: MissingLength::One,
+            strict: false,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GraphAuxilliary {
     pub node2id: HashMap<Vec<u8>, ItemId>,
     pub node_len_ary: Vec<ItemIdSize>,
     pub edge2id: Option<HashMap<Edge, ItemId>>,
+    // edges traversed by paths/walks, collected along with the L-lines if edges are indexed
+    pub path_edges: Option<HashSet<Edge>>,
     pub path_segments: Vec<PathSegment>,
     pub node_count: usize,
     pub edge_count: usize,
+    // number of L-lines in GFA, available even if edges are not indexed
+    pub link_count: usize,
+    // names of L-lines that refer to undefined segments, available if edges are indexed
+    pub dangling_links: Vec<String>,
+    // prefix that is stripped from node IDs upon indexing and look-up
+    pub node_prefix: Vec<u8>,
+    // whether node and path IDs are URL-decoded upon indexing and look-up
+    pub decode_names: bool,
+    // whether leading zeros are removed from numeric node IDs upon indexing and look-up
+    pub normalize_ids: bool,
+    // number of look-ups that succeeded only after normalizing the node ID
+    pub normalized_lookups: Arc<AtomicUsize>,
+    // per-node depth as given by the optional S-line tag, if requested
+    pub node_depth: Option<Vec<Option<f64>>>,
+    // whether edges are identified by their endpoints only, irrespective of orientation
+    pub undirected_edges: bool,
+    // whether warnings about the graph are treated as errors
+    pub strict: bool,
+    // alleles of simple bubbles, identified upon first request and reset whenever edges change
+    alleles: Arc<Mutex<Option<Arc<HashSet<ItemIdSize>>>>>,
 }
 
 impl GraphAuxilliary {
@@ -160,33 +250,173 @@ impl GraphAuxilliary {
         path_segments: Vec<PathSegment>,
         node_count: usize,
         edge_count: usize,
+        link_count: usize,
+        node_prefix: Vec<u8>,
     ) -> Self {
         Self {
             node2id,
             node_len_ary,
             edge2id,
+            path_edges: None,
             path_segments,
             node_count,
             edge_count,
+            link_count,
+            dangling_links: Vec::new(),
+            node_prefix,
+            decode_names: false,
+            normalize_ids: false,
+            normalized_lookups: Arc::new(AtomicUsize::new(0)),
+            node_depth: None,
+            undirected_edges: false,
+            strict: false,
+            alleles: Arc::new(Mutex::new(None)),
         }
     }
 
     pub fn from_gfa<R: std::io::Read>(
         data: &mut std::io::BufReader<R>,
-        index_edges: bool,
+        options: &IndexOptions,
     ) -> Result<Self, std::io::Error> {
-        let (node2id, node_len_ary, edges, path_segments) = io::parse_graph_aux(data, index_edges)?;
+        let (node2id, node_len_ary, edges, path_edges, link_count, path_segments, node_depth) =
+            io::parse_graph_aux(data, options)?;
         // don't count "0" ID
         let nc = node_len_ary.len() - 1;
-        let (edge2id, ec) = Self::construct_edgemap(edges, &node2id);
-        Ok(Self::new(
+        let mut res = Self::new(
             node2id,
             node_len_ary,
-            edge2id,
+            None,
             path_segments,
             nc,
-            ec,
-        ))
+            0,
+            link_count,
+            options.strip_prefix.as_bytes().to_vec(),
+        );
+        // must be set before edges are indexed, as L-lines refer to (encoded) node IDs
+        res.decode_names = options.decode_names;
+        res.normalize_ids = options.normalize_ids;
+        res.strict = options.strict;
+        let (edge2id, ec, dangling_links) = res.construct_edgemap(edges)?;
+        res.edge2id = edge2id;
+        res.edge_count = ec;
+        res.dangling_links = dangling_links;
+        // steps on unknown nodes are reported when the paths/walks are parsed for counting
+        res.path_edges = path_edges.map(|es| {
+            es.iter()
+                .filter_map(|e| {
+                    let k = e.iter().position(|&x| x == b'\t').unwrap();
+                    let u = res.node_id(&e[1..k])?;
+                    let v = res.node_id(&e[k + 2..])?;
+                    Some(Edge::canonical(
+                        *u,
+                        Orientation::from_lg(e[0]),
+                        *v,
+                        Orientation::from_lg(e[k + 1]),
+                    ))
+                })
+                .collect()
+        });
+        res.report_normalized_lookups()?;
+        if let Some(depths) = &node_depth {
+            let missing = depths.iter().skip(1).filter(|x| x.is_none()).count();
+            if missing > 0 {
+                warn_or_fail(
+                    options.strict,
+                    &format!(
+                        "{} segments lack depth tag {}, they will be excluded from counting",
+                        missing, options.depth_tag
+                    ),
+                )?;
+            }
+        }
+        res.node_depth = node_depth;
+        Ok(res)
+    }
+
+    pub fn node_id(&self, node: &[u8]) -> Option<&ItemId> {
+        let node = if self.decode_names {
+            percent_decode(node)
+        } else {
+            node.into()
+        };
+        let node = node.strip_prefix(&self.node_prefix[..]).unwrap_or(&node);
+        if self.normalize_ids {
+            let normalized = normalize_id(node);
+            if normalized.len() < node.len() {
+                self.normalized_lookups.fetch_add(1, Ordering::Relaxed);
+            }
+            self.node2id.get(&normalized[..])
+        } else {
+            self.node2id.get(node)
+        }
+    }
+
+    pub fn report_normalized_lookups(&self) -> Result<(), std::io::Error> {
+        let c = self.normalized_lookups.swap(0, Ordering::Relaxed);
+        if c > 0 {
+            warn_or_fail(
+                self.strict,
+                &format!(
+                    "{} references to segments were resolved by removing leading zeros from their IDs",
+                    c
+                ),
+            )?;
+        }
+        Ok(())
+    }
+
+    // key of an edge in edge2id
+    pub fn edge_key(&self, e: Edge) -> Edge {
+        if self.undirected_edges {
+            Edge(
+                ItemId(e.0 .0.min(e.2 .0)),
+                Orientation::Forward,
+                ItemId(e.0 .0.max(e.2 .0)),
+                Orientation::Forward,
+            )
+        } else {
+            e
+        }
+    }
+
+    pub fn set_undirected_edges(&mut self) -> usize {
+        // edges connecting the same pair of nodes are merged, IDs are re-assigned in the order of
+        // the first of the merged edges; returns the number of edges after merging
+        self.undirected_edges = true;
+        self.alleles = Arc::new(Mutex::new(None));
+        if let Some(edge2id) = self.edge2id.take() {
+            let mut edges: Vec<(Edge, ItemId)> = edge2id.into_iter().collect();
+            edges.sort_by_key(|(_, id)| id.0);
+            let mut res: HashMap<Edge, ItemId> = HashMap::default();
+            for (e, _) in edges {
+                let k = self.edge_key(e);
+                if !res.contains_key(&k) {
+                    let id = ItemId(res.len() as ItemIdSize + 1);
+                    res.insert(k, id);
+                }
+            }
+            self.edge_count = res.len();
+            self.edge2id = Some(res);
+        }
+        self.edge_count
+    }
+
+    // look up an edge given in the notation of edge_name, e.g., >1<2
+    pub fn edge_id(&self, name: &str) -> Option<ItemId> {
+        let b = name.as_bytes();
+        if b.is_empty() || (b[0] != b'>' && b[0] != b'<') {
+            return None;
+        }
+        let k = b[1..].iter().position(|&x| x == b'>' || x == b'<')? + 1;
+        let u = self.node_id(&b[1..k])?;
+        let v = self.node_id(&b[k + 1..])?;
+        let e = self.edge_key(Edge::canonical(
+            *u,
+            Orientation::from_lg(b[0]),
+            *v,
+            Orientation::from_lg(b[k]),
+        ));
+        self.edge2id.as_ref()?.get(&e).copied()
     }
 
     pub fn node_len(&self, v: &ItemId) -> ItemIdSize {
@@ -203,33 +433,344 @@ impl GraphAuxilliary {
         self.edge_count
     }
 
+    pub fn total_bp(&self) -> usize {
+        self.node_len_ary[1..].iter().map(|&l| l as usize).sum()
+    }
+
+    // size of the entire graph in terms of the given count type, irrespective of path coverage
+    pub fn graph_size(&self, count: CountType) -> f64 {
+        match count {
+            CountType::Node => self.node_count as f64,
+            CountType::Edge => self.edge_count as f64,
+            CountType::Bp => self.total_bp() as f64,
+            CountType::Allele => self.bubble_alleles().len() as f64,
+        }
+    }
+
+    pub fn node_len_n50(&self) -> usize {
+        let mut lens: Vec<usize> = self.node_len_ary[1..].iter().map(|&l| l as usize).collect();
+        lens.sort_unstable_by(|a, b| b.cmp(a));
+        let half = (self.total_bp() + 1) / 2;
+        let mut acc = 0;
+        for l in lens {
+            acc += l;
+            if acc >= half {
+                return l;
+            }
+        }
+        0
+    }
+
+    pub fn stats_summary(&self) -> String {
+        let samples: HashSet<&String> = self.path_segments.iter().map(|x| &x.sample).collect();
+        let haplotypes: HashSet<(&String, &Option<String>)> = self
+            .path_segments
+            .iter()
+            .map(|x| (&x.sample, &x.haplotype))
+            .collect();
+        let total_bp = self.total_bp();
+        format!(
+            "graph has {} nodes, {} edges (L-lines), {} bp in total, average node length {:.2} bp, node length N50 {} bp; {} paths/walks from {} samples and {} haplotypes",
+            self.node_count,
+            self.link_count,
+            total_bp,
+            if self.node_count > 0 {
+                total_bp as f64 / self.node_count as f64
+            } else {
+                0.0
+            },
+            self.node_len_n50(),
+            self.path_segments.len(),
+            samples.len(),
+            haplotypes.len()
+        )
+    }
+
+    pub fn id2node(&self) -> Vec<String> {
+        // create mapping from numerical node ids to node identifiers as indexed, i.e., without
+        // the prefix that might have been stripped while parsing, so that IDs of graphs that
+        // differ only in their prefix align
+        let mut id2node: Vec<String> = vec![String::new(); self.number_of_nodes() + 1];
+        for (node, id) in self.node2id.iter() {
+            id2node[id.0 as usize] = str::from_utf8(node).unwrap().to_string();
+        }
+        id2node
+    }
+
+    pub fn edge_name(&self, id2node: &[String], edge: &Edge) -> String {
+        format!(
+            "{}{}{}{}",
+            edge.1, &id2node[edge.0 .0 as usize], edge.3, &id2node[edge.2 .0 as usize]
+        )
+    }
+
+    // indexes the edges traversed by paths/walks that are not represented by L-lines; returns
+    // their number
+    pub fn add_path_edges(&mut self) -> usize {
+        let path_edges = self
+            .path_edges
+            .as_ref()
+            .expect("adding path edges requires path edges in GraphAuxilliary");
+        let edge2id = self
+            .edge2id
+            .as_ref()
+            .expect("adding path edges requires edge2id map in GraphAuxilliary");
+        let mut path_only: Vec<Edge> = path_edges
+            .iter()
+            .map(|e| self.edge_key(*e))
+            .filter(|e| !edge2id.contains_key(e))
+            .collect();
+        // assign IDs in a deterministic order
+        path_only.sort();
+        path_only.dedup();
+        self.alleles = Arc::new(Mutex::new(None));
+        let edge2id = self.edge2id.as_mut().unwrap();
+        for e in path_only.iter() {
+            self.edge_count += 1;
+            edge2id.insert(*e, ItemId(self.edge_count as ItemIdSize));
+        }
+        path_only.len()
+    }
+
+    pub fn write_edge_audit<W: Write>(&self, out: &mut BufWriter<W>) -> Result<(), std::io::Error> {
+        let link_edges = self
+            .edge2id
+            .as_ref()
+            .expect("edge audit requires edge2id map in GraphAuxilliary");
+        let path_edges = self
+            .path_edges
+            .as_ref()
+            .expect("edge audit requires path edges in GraphAuxilliary");
+
+        let id2node = self.id2node();
+
+        let mut path_only: Vec<&Edge> = path_edges
+            .iter()
+            .filter(|e| !link_edges.contains_key(e))
+            .collect();
+        path_only.sort();
+        let mut link_only: Vec<&Edge> = link_edges
+            .keys()
+            .filter(|e| !path_edges.contains(e))
+            .collect();
+        link_only.sort();
+        log::info!(
+            "found {} edges traversed by paths/walks without L-line, {} L-lines not traversed by any path/walk, and {} L-lines referring to undefined segments",
+            path_only.len(),
+            link_only.len(),
+            self.dangling_links.len()
+        );
+
+        writeln!(out, "category\tedge")?;
+        for (category, edges) in [("path-only", path_only), ("link-only", link_only)] {
+            for edge in edges {
+                writeln!(out, "{}\t{}", category, self.edge_name(&id2node, edge))?;
+            }
+        }
+        for link in self.dangling_links.iter() {
+            writeln!(out, "dangling\t{}", link)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_private_bed<W: Write>(
+        &self,
+        path_items: &[Vec<ItemIdSize>],
+        coverage: &[CountSize],
+        reference: &PathSegment,
+        out: &mut BufWriter<W>,
+    ) -> Result<(), std::io::Error> {
+        // path_items lists the nodes visited by each path in the order of path_segments; nodes of
+        // the reference are private if its group is the only one covering them, nodes that are
+        // not counted at all (e.g., outside of subset coordinates) are not
+        let shared = |i: ItemIdSize| coverage[i as usize] != 1;
+
+        // consecutive private nodes are reported as one interval; positions are relative to the
+        // start of the path, or to the start of the path segment if it has coordinates
+        let chrom = reference.id();
+        let mut c = 0;
+        for (path_seg, items) in self
+            .path_segments
+            .iter()
+            .zip(path_items)
+            .filter(|(x, _)| &x.clear_coords() == reference)
+        {
+            let mut pos = path_seg.coords().map(|(start, _)| start).unwrap_or(0);
+            let mut start: Option<usize> = None;
+            for &i in items {
+                if shared(i) {
+                    if let Some(s) = start.take() {
+                        writeln!(out, "{}\t{}\t{}", &chrom, s, pos)?;
+                        c += 1;
+                    }
+                } else if start.is_none() {
+                    start = Some(pos);
+                }
+                pos += self.node_len_ary[i as usize] as usize;
+            }
+            if let Some(s) = start {
+                writeln!(out, "{}\t{}\t{}", &chrom, s, pos)?;
+                c += 1;
+            }
+        }
+        log::info!("found {} private intervals", c);
+        Ok(())
+    }
+
+    pub fn write_node_len_hist<W: Write>(
+        &self,
+        bin_width: usize,
+        log_bins: bool,
+        out: &mut BufWriter<W>,
+    ) -> Result<(), std::io::Error> {
+        // bin b covers lengths [b*w, (b+1)*w-1]; logarithmic bin b > 0 covers [2^(b-1), 2^b-1], and
+        // logarithmic bin 0 holds nodes of length 0
+        let bin = |l: usize| {
+            if log_bins {
+                (usize::BITS - l.leading_zeros()) as usize
+            } else {
+                l / bin_width
+            }
+        };
+        let range = |b: usize| {
+            if !log_bins {
+                (b * bin_width, (b + 1) * bin_width - 1)
+            } else if b == 0 {
+                (0, 0)
+            } else {
+                (1 << (b - 1), (1 << b) - 1)
+            }
+        };
+
+        // number of nodes and their total length per bin; the first entry of node_len_ary is a
+        // placeholder
+        let mut hist: Vec<(usize, usize)> = Vec::new();
+        for &l in self.node_len_ary[1..].iter() {
+            let b = bin(l as usize);
+            if b >= hist.len() {
+                hist.resize(b + 1, (0, 0));
+            }
+            hist[b].0 += 1;
+            hist[b].1 += l as usize;
+        }
+
+        writeln!(out, "from\tto\tnodes\tbp")?;
+        for (b, (nodes, bp)) in hist.into_iter().enumerate() {
+            let (from, to) = range(b);
+            writeln!(out, "{}\t{}\t{}\t{}", from, to, nodes, bp)?;
+        }
+        Ok(())
+    }
+
+    pub fn bubble_alleles(&self) -> Arc<HashSet<ItemIdSize>> {
+        // the graph is scanned for bubbles only once, later calls share the result
+        let mut alleles = self.alleles.lock().unwrap();
+        alleles
+            .get_or_insert_with(|| Arc::new(self.find_bubble_alleles()))
+            .clone()
+    }
+
+    fn find_bubble_alleles(&self) -> HashSet<ItemIdSize> {
+        //
+        // identifies simple bubbles, i.e., a source s and a sink t that are connected by two or more
+        // parallel paths that each consist of a single node (the allele) or a direct edge from s to
+        // t; returns the IDs of all allele nodes
+        //
+        let mut succ: HashMap<(ItemIdSize, Orientation), Vec<(ItemIdSize, Orientation)>> =
+            HashMap::default();
+        for e in self
+            .edge2id
+            .as_ref()
+            .expect("bubble detection requires edge2id map in GraphAuxilliary")
+            .keys()
+        {
+            for x in [*e, e.flip()] {
+                succ.entry((x.0 .0, x.1))
+                    .or_insert(Vec::new())
+                    .push((x.2 .0, x.3));
+            }
+        }
+        succ.values_mut().for_each(|v| {
+            v.sort();
+            v.dedup();
+        });
+
+        let empty = Vec::new();
+        let successors = |v: &(ItemIdSize, Orientation)| succ.get(v).unwrap_or(&empty);
+        let predecessors = |v: &(ItemIdSize, Orientation)| -> Vec<(ItemIdSize, Orientation)> {
+            successors(&(v.0, v.1.flip()))
+                .iter()
+                .map(|&(u, o)| (u, o.flip()))
+                .collect()
+        };
+
+        let mut res = HashSet::new();
+        for (s, out) in succ.iter() {
+            if out.len() < 2 {
+                continue;
+            }
+            // candidate alleles have s as single predecessor and a single successor
+            let alleles: Vec<&(ItemIdSize, Orientation)> = out
+                .iter()
+                .filter(|a| a.0 != s.0 && successors(a).len() == 1 && predecessors(a) == vec![*s])
+                .collect();
+            if alleles.is_empty() {
+                continue;
+            }
+            let t = successors(alleles[0])[0];
+            if t.0 == s.0 || alleles.iter().any(|a| successors(a)[0] != t) {
+                continue;
+            }
+            // all other successors of s must be the sink itself (deletion allele), and all
+            // predecessors of the sink must be either alleles or s
+            let has_deletion = out.contains(&t);
+            if out.len() != alleles.len() + has_deletion as usize
+                || predecessors(&t).len() != out.len()
+                || alleles.len() + (has_deletion as usize) < 2
+            {
+                continue;
+            }
+            res.extend(alleles.iter().map(|a| a.0));
+        }
+        res
+    }
+
     pub fn number_of_items(&self, c: &CountType) -> usize {
         match c {
-            &CountType::Node | &CountType::Bp => self.node_count,
+            &CountType::Node | &CountType::Bp | &CountType::Allele => self.node_count,
             &CountType::Edge => self.edge_count,
         }
     }
 
     pub fn construct_edgemap(
+        &self,
         edges: Option<Vec<Vec<u8>>>,
-        node2id: &HashMap<Vec<u8>, ItemId>,
-    ) -> (Option<HashMap<Edge, ItemId>>, usize) {
+    ) -> Result<(Option<HashMap<Edge, ItemId>>, usize, Vec<String>), std::io::Error> {
+        let mut dangling = Vec::new();
         match edges {
             Some(es) => {
                 let mut res = HashMap::default();
                 let mut c: ItemIdSize = 0;
                 for b in es {
-                    let e = Edge::from_link(&b[..], node2id, true);
+                    let e = match Edge::from_link(&b[..], self, true) {
+                        Ok(e) => e,
+                        Err(e) => {
+                            // dangling links are not indexed, but reported in the edge audit
+                            warn_or_fail(self.strict, &format!("{}, ignoring", e))?;
+                            dangling.push(Edge::link_name(&b[..]));
+                            continue;
+                        }
+                    };
                     if res.contains_key(&e) {
-                        log::error!("edge {} is duplicated in GFA", &e);
+                        warn_or_fail(self.strict, &format!("edge {} is duplicated in GFA", &e))?;
                     } else {
                         c += 1;
                         res.insert(e, ItemId(c));
                     }
                 }
-                (Some(res), c as usize)
+                Ok((Some(res), c as usize, dangling))
             }
-            None => (None, 0),
+            None => Ok((None, 0, dangling)),
         }
     }
 }
@@ -5,10 +5,11 @@ use std::io::Write;
 use crate::abacus::AbacusByTotal;
 use crate::cli;
 use crate::io;
-use crate::util::{CountType, Threshold};
+use crate::util::{open_file, CountType, Threshold};
 
 #[derive(Debug, Clone)]
 pub struct Hist {
+    pub count: CountType,
     pub coverage: Vec<usize>,
 }
 
@@ -29,40 +30,127 @@ pub fn choose(n: usize, k: usize) -> f64 {
     res
 }
 
+// fraction of the countables at a step of the growth curve that have not been present in the
+// previous step
+pub fn growth_efficiency(prev: f64, cur: f64) -> f64 {
+    if cur > 0.0 {
+        (cur - prev) / cur
+    } else {
+        0.0
+    }
+}
+
+// least-squares fit of n(N) = k * exp(-N / tau) + omega to the given curve at N = 1, 2, ...;
+// for fixed tau the model is linear in k and omega, which are then solved for directly, so that
+// only tau needs to be searched for; returns (k, tau, omega, residual sum of squares)
+pub fn fit_tettelin_core(curve: &[f64]) -> (f64, f64, f64, f64) {
+    let n = curve.len() as f64;
+    let fit = |tau: f64| {
+        let xs: Vec<f64> = (1..=curve.len())
+            .map(|i| (-(i as f64) / tau).exp())
+            .collect();
+        let mx = xs.iter().sum::<f64>() / n;
+        let my = curve.iter().sum::<f64>() / n;
+        let sxx: f64 = xs.iter().map(|x| (x - mx) * (x - mx)).sum();
+        let sxy: f64 = xs.iter().zip(curve).map(|(x, y)| (x - mx) * (y - my)).sum();
+        let k = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+        let omega = my - k * mx;
+        let rss: f64 = xs
+            .iter()
+            .zip(curve)
+            .map(|(x, y)| (y - k * x - omega) * (y - k * x - omega))
+            .sum();
+        (k, omega, rss)
+    };
+
+    // coarse grid search over log(tau), followed by golden-section search around the best value
+    let (lo, hi) = ((0.01f64).ln(), (1000.0 * n).ln());
+    let steps = 200;
+    let step = (hi - lo) / steps as f64;
+    let best = (0..=steps)
+        .map(|i| lo + i as f64 * step)
+        .min_by(|a, b| fit(a.exp()).2.partial_cmp(&fit(b.exp()).2).unwrap())
+        .unwrap();
+    let (mut a, mut b) = (best - step, best + step);
+    let r = (5f64.sqrt() - 1.0) / 2.0;
+    for _ in 0..100 {
+        let c = b - r * (b - a);
+        let d = a + r * (b - a);
+        if fit(c.exp()).2 < fit(d.exp()).2 {
+            b = d;
+        } else {
+            a = c;
+        }
+    }
+    let tau = ((a + b) / 2.0).exp();
+    let (k, omega, rss) = fit(tau);
+    (k, tau, omega, rss)
+}
+
 impl Hist {
     pub fn from_tsv<R: std::io::Read>(
         data: &mut std::io::BufReader<R>,
+        count: Option<CountType>,
     ) -> Result<Self, std::io::Error> {
-        let coverage = io::parse_hist(data)?;
-        Ok(Self { coverage })
+        let (header_count, coverage) = io::parse_hist(data)?;
+        let count = match (header_count, count) {
+            (Some(h), Some(c)) if h != c => {
+                let msg = format!(
+                    "histogram reports counts of type \"{}\", but \"{}\" was requested",
+                    h, c
+                );
+                log::error!("{}", &msg);
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+            }
+            (Some(c), _) | (None, Some(c)) => c,
+            (None, None) => {
+                log::info!("histogram does not specify count type, assuming \"node\"");
+                CountType::Node
+            }
+        };
+        Ok(Self { count, coverage })
     }
 
     pub fn from_abacus(abacus: &AbacusByTotal) -> Self {
         Self {
+            count: abacus.count,
             coverage: match abacus.count {
                 CountType::Node | CountType::Edge => abacus.construct_hist(),
                 CountType::Bp => abacus.construct_hist_bps(),
+                CountType::Allele => abacus.construct_hist_alleles(),
             },
         }
     }
 
     pub fn calc_growth(&self, t_coverage: &Threshold, t_quorum: &Threshold) -> Vec<f64> {
+        let mut pangrowth: Vec<f64> = Vec::with_capacity(self.coverage.len() - 1);
+        self.calc_growth_with(t_coverage, t_quorum, |y| pangrowth.push(y));
+        pangrowth
+    }
+
+    // passes the values of the growth curve for 1, 2, ..., n groups to emit as soon as they are
+    // computed, so that the caller does not need to hold the entire curve in memory
+    pub fn calc_growth_with<F: FnMut(f64)>(
+        &self,
+        t_coverage: &Threshold,
+        t_quorum: &Threshold,
+        emit: F,
+    ) {
         let n = self.coverage.len() - 1;
         let quorum = usize::max(1, t_quorum.to_absolute(n));
         if quorum == 1 {
-            self.calc_growth_union(t_coverage)
+            self.calc_growth_union(t_coverage, emit)
         } else if quorum >= n {
-            self.calc_growth_core(t_coverage)
+            self.calc_growth_core(t_coverage, emit)
         } else {
-            self.calc_growth_quorum(t_coverage, t_quorum)
+            self.calc_growth_quorum(t_coverage, t_quorum, emit)
         }
     }
 
-    pub fn calc_growth_union(&self, t_coverage: &Threshold) -> Vec<f64> {
+    fn calc_growth_union<F: FnMut(f64)>(&self, t_coverage: &Threshold, mut emit: F) {
         let n = self.coverage.len() - 1; // hist array has length n+1: from 0..n (both included)
         let c = usize::max(1, t_coverage.to_absolute(n));
 
-        let mut pangrowth: Vec<f64> = vec![0.0; n];
         let mut n_fall_m: f64 = 0.0;
         let tot = self.coverage[c..].iter().sum::<usize>() as f64;
 
@@ -79,17 +167,14 @@ impl Hist {
                 y += ((self.coverage[i] as f64).log2() + perc_mult[i] - n_fall_m).exp2();
             }
 
-            pangrowth[m - 1] = tot - y;
+            emit(tot - y);
         }
-
-        pangrowth
     }
 
-    pub fn calc_growth_core(&self, t_coverage: &Threshold) -> Vec<f64> {
+    fn calc_growth_core<F: FnMut(f64)>(&self, t_coverage: &Threshold, mut emit: F) {
         let n = self.coverage.len() - 1; // hist array has length n+1: from 0..n (both included)
         let c = usize::max(1, t_coverage.to_absolute(n + 1));
         let mut n_fall_m: f64 = 0.0;
-        let mut pangrowth: Vec<f64> = vec![0.0; n];
 
         // In perc_mult[i] is contained the percentage of combinations
         // that have an item of multiplicity i
@@ -103,17 +188,19 @@ impl Hist {
                 perc_mult[i] += (i as f64 - m as f64 + 1.0).log2();
                 y += ((self.coverage[i] as f64).log2() + perc_mult[i] - n_fall_m).exp2();
             }
-            pangrowth[m - 1] = y;
+            emit(y);
         }
-
-        pangrowth
     }
 
-    pub fn calc_growth_quorum(&self, t_coverage: &Threshold, t_quorum: &Threshold) -> Vec<f64> {
+    fn calc_growth_quorum<F: FnMut(f64)>(
+        &self,
+        t_coverage: &Threshold,
+        t_quorum: &Threshold,
+        mut emit: F,
+    ) {
         let n = self.coverage.len() - 1; // hist array has length n+1: from [0..n]
         let c = usize::max(1, t_coverage.to_absolute(n));
         let quorum = t_quorum.to_relative(n);
-        let mut pangrowth: Vec<f64> = vec![0.0; n];
 
         let mut n_fall_m: f64 = 0.0;
         let mut m_fact: f64 = 0.0;
@@ -154,21 +241,196 @@ impl Hist {
                         .exp2();
                 }
             }
-            pangrowth[m - 1] = yl + yr;
+            emit(yl + yr);
         }
-        pangrowth
     }
 
     pub fn to_tsv<W: std::io::Write>(
         &self,
-        count: &CountType,
+        all_cumulatives: bool,
+        min_coverage_bin: usize,
+        multiplicity: bool,
+        out: &mut std::io::BufWriter<W>,
+    ) -> Result<(), std::io::Error> {
+        if multiplicity {
+            writeln!(
+                out,
+                "# coverage: number of traversals of a countable by the paths of all groups; 0 = not traversed (e.g., if excluded)"
+            )?;
+        } else {
+            // bins range from 0 to the number of groups
+            let n = self.coverage.len().max(1) - 1;
+            writeln!(
+                out,
+                "# coverage: number of groups that cover a countable; 0 = covered by no group (e.g., if excluded), {} = covered by all {} groups",
+                n, n
+            )?;
+        }
+        // histograms of traversals are marked as such in the header, so that they are not mistaken
+        // for coverage histograms when read back
+        let first_col = if multiplicity {
+            "traversals"
+        } else {
+            "coverage"
+        };
+        if all_cumulatives {
+            writeln!(
+                out,
+                "{}\t{}\tcumulative_at_least\tcumulative_at_most",
+                first_col, self.count
+            )?;
+            let total: usize = self.coverage.iter().sum();
+            let mut at_most = 0;
+            for (i, c) in self.coverage.iter().enumerate() {
+                // items with coverage >= i are all items except those with coverage < i
+                let at_least = total - at_most;
+                at_most += c;
+                if i >= min_coverage_bin {
+                    writeln!(out, "{}\t{}\t{}\t{}", i, c, at_least, at_most)?;
+                }
+            }
+        } else {
+            writeln!(out, "{}\t{}", first_col, self.count)?;
+            for (i, c) in self.coverage.iter().enumerate().skip(min_coverage_bin) {
+                writeln!(out, "{}\t{}", i, c)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn merge_to_tsv<W: std::io::Write>(
+        hists: &[Hist],
+        names: &[String],
+        out: &mut std::io::BufWriter<W>,
+    ) -> Result<(), std::io::Error> {
+        if let Some(h) = hists.iter().find(|h| h.count != hists[0].count) {
+            let msg = format!(
+                "cannot merge histograms of different count types (\"{}\" and \"{}\")",
+                hists[0].count, h.count
+            );
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+        }
+
+        write!(out, "coverage")?;
+        for name in names {
+            write!(out, "\t{}", name)?;
+        }
+        writeln!(out, "")?;
+
+        // bins are the union of all histograms' bins, missing bins are filled with zeros
+        let n = hists.iter().map(|h| h.coverage.len()).max().unwrap_or(0);
+        for i in 0..n {
+            write!(out, "{}", i)?;
+            for h in hists {
+                write!(out, "\t{}", h.coverage.get(i).unwrap_or(&0))?;
+            }
+            writeln!(out, "")?;
+        }
+
+        Ok(())
+    }
+
+    pub fn at_coverage_to_tsv<W: std::io::Write>(
+        &self,
+        from: usize,
+        to: usize,
         out: &mut std::io::BufWriter<W>,
     ) -> Result<(), std::io::Error> {
-        writeln!(out, "coverage\t{}", count)?;
-        for (i, c) in self.coverage.iter().enumerate() {
-            writeln!(out, "{}\t{}", i, c)?;
+        // histogram has bins for coverage 0..n, where n is the number of groups
+        let n = self.coverage.len() - 1;
+        if to > n {
+            let msg = format!(
+                "coverage level {} exceeds the number of groups ({}) of the histogram",
+                to, n
+            );
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+        }
+
+        writeln!(out, "coverage\t{}", self.count)?;
+        if from == to {
+            write!(out, "{}", from)?;
+        } else {
+            write!(out, "{}-{}", from, to)?;
+        }
+        writeln!(out, "\t{}", self.coverage[from..=to].iter().sum::<usize>())?;
+        Ok(())
+    }
+
+    pub fn observed(&self) -> usize {
+        // countables of coverage 0 are not seen by any group
+        self.coverage.iter().skip(1).sum()
+    }
+
+    pub fn chao1(&self) -> f64 {
+        let f1 = *self.coverage.get(1).unwrap_or(&0) as f64;
+        let f2 = *self.coverage.get(2).unwrap_or(&0) as f64;
+        // bias-corrected form if there are no doubletons
+        let unseen = if f2 > 0.0 {
+            f1 * f1 / (2.0 * f2)
+        } else {
+            f1 * (f1 - 1.0).max(0.0) / 2.0
+        };
+        self.observed() as f64 + unseen
+    }
+
+    pub fn ace(&self, rare_threshold: usize) -> f64 {
+        let k = usize::min(rare_threshold, self.coverage.len() - 1);
+        let abundant: usize = self.coverage.iter().skip(k + 1).sum();
+        let rare: usize = self.coverage[1..=k].iter().sum();
+        let f1 = *self.coverage.get(1).unwrap_or(&0) as f64;
+        let n_rare: f64 = (1..=k).map(|i| (i * self.coverage[i]) as f64).sum();
+        if rare == 0 {
+            return abundant as f64;
+        }
+        // sample coverage estimate of the rare countables; undefined if all of them are singletons
+        let c = 1.0 - f1 / n_rare;
+        if c <= 0.0 {
+            return f64::NAN;
         }
+        let gamma = if n_rare > 1.0 {
+            f64::max(
+                rare as f64 / c
+                    * (1..=k)
+                        .map(|i| (i * (i - 1) * self.coverage[i]) as f64)
+                        .sum::<f64>()
+                    / (n_rare * (n_rare - 1.0))
+                    - 1.0,
+                0.0,
+            )
+        } else {
+            0.0
+        };
+        abundant as f64 + rare as f64 / c + f1 / c * gamma
+    }
 
+    pub fn estimate_to_tsv<W: std::io::Write>(
+        &self,
+        rare_threshold: usize,
+        out: &mut std::io::BufWriter<W>,
+    ) -> Result<(), std::io::Error> {
+        if rare_threshold == 0 {
+            let msg = "rare threshold must be at least 1";
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+        }
+        // the estimators are undefined if no countable is covered at all
+        if self.coverage.len() < 2 {
+            let msg = "cannot estimate pangenome size from an empty histogram";
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+        }
+        writeln!(out, "count\tobserved\tchao1\tace")?;
+        writeln!(
+            out,
+            "{}\t{}\t{:.4}\t{:.4}",
+            self.count,
+            self.observed(),
+            self.chao1(),
+            self.ace(rare_threshold)
+        )?;
         Ok(())
     }
 }
@@ -180,21 +442,76 @@ pub struct HistAuxilliary {
 
 impl HistAuxilliary {
     pub fn from_params(params: &cli::Params) -> Result<Self, std::io::Error> {
-        match params {
-            cli::Params::Histgrowth {
-                quorum, coverage, ..
+        let cli::GrowthArgs {
+            quorum,
+            coverage,
+            soft_core,
+            curves,
+            thresholds_file,
+            ..
+        } = match params.growth_args() {
+            Some(args) => args,
+            None => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    "not implemented",
+                ))
             }
-            | cli::Params::Growth {
-                quorum, coverage, ..
+        };
+        let mut res = if thresholds_file.is_empty() {
+            Self::load(quorum, coverage)?
+        } else {
+            Self::load_file(thresholds_file)?
+        };
+        if let Some(f) = soft_core {
+            if *f <= 0.0 || *f > 1.0 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("soft-core fraction \"{}\" must be within (0,1]", f),
+                ));
             }
-            | cli::Params::OrderedHistgrowth {
-                quorum, coverage, ..
-            } => Self::load(quorum, coverage),
-            _ => Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "not implemented",
-            )),
+            res.add_pair(Threshold::Absolute(1), Threshold::Relative(*f));
+        }
+        for curve in curves {
+            let (c, q) = curve.thresholds();
+            res.add_pair(c, q);
+        }
+        Ok(res)
+    }
+
+    fn add_pair(&mut self, coverage: Threshold, quorum: Threshold) {
+        // don't report the same curve twice
+        if !self
+            .coverage
+            .iter()
+            .zip(&self.quorum)
+            .any(|(c, q)| c == &coverage && q == &quorum)
+        {
+            log::debug!(
+                "adding coverage/quorum pair {}/{} to thresholds",
+                coverage,
+                quorum
+            );
+            self.coverage.push(coverage);
+            self.quorum.push(quorum);
+        }
+    }
+
+    fn load_file(file_name: &str) -> Result<Self, std::io::Error> {
+        log::info!("loading coverage/quorum thresholds from {}", file_name);
+        let mut data = open_file(file_name)?;
+        let (coverage, quorum): (Vec<Threshold>, Vec<Threshold>) =
+            io::parse_threshold_file(&mut data)?.into_iter().unzip();
+        if coverage.is_empty() {
+            let msg = format!(
+                "threshold file {} contains no coverage/quorum pair",
+                file_name
+            );
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
         }
+        log::debug!("loaded {} coverage/quorum pairs", coverage.len());
+        Ok(Self { quorum, coverage })
     }
 
     fn load(quorum: &str, coverage: &str) -> Result<Self, std::io::Error> {
@@ -222,7 +539,7 @@ impl HistAuxilliary {
         let mut coverage_thresholds = Vec::new();
         if !coverage.is_empty() {
             coverage_thresholds =
-                cli::parse_threshold_cli(&coverage[..], cli::RequireThreshold::Absolute)?;
+                cli::parse_threshold_cli(&coverage[..], cli::RequireThreshold::Either)?;
             log::debug!(
                 "loaded {} coverage thresholds: {}",
                 coverage_thresholds.len(),
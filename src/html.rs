@@ -0,0 +1,221 @@
+/* standard use */
+use std::io::{BufWriter, Write};
+
+/* private use */
+use crate::graph::GraphAuxilliary;
+use crate::hist::{Hist, HistAuxilliary};
+use crate::util::{json_escape, Rounding};
+
+//
+// self-contained HTML report; data is embedded as JSON and rendered as SVG by the script below
+//
+const REPORT_TEMPLATE: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>panacus report</title>
+<style>
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.5em; }
+h2 { font-size: 1.2em; margin-top: 2em; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #ccc; padding: 0.3em 0.8em; text-align: left; }
+.legend span { cursor: pointer; margin-right: 1.5em; }
+.legend span.off { opacity: 0.3; }
+svg text { font-size: 11px; }
+code { background: #f4f4f4; padding: 0.1em 0.3em; }
+</style>
+</head>
+<body>
+<h1>panacus report</h1>
+<p><code id="command"></code></p>
+<h2>Statistics</h2>
+<table id="stats"></table>
+<h2>Coverage histogram</h2>
+<div id="hist"></div>
+<h2>Pangenome growth</h2>
+<div class="legend" id="legend"></div>
+<div id="growth"></div>
+<script>
+const DATA = {{DATA}};
+const COLORS = ["#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f"];
+const NS = "http://www.w3.org/2000/svg";
+
+function el(name, attrs, parent) {
+  const e = document.createElementNS(NS, name);
+  for (const k in attrs) e.setAttribute(k, attrs[k]);
+  if (parent) parent.appendChild(e);
+  return e;
+}
+
+function axes(svg, w, h, m, xs, ymax, xlabel, ylabel) {
+  el("line", {x1: m, y1: h - m, x2: w - m, y2: h - m, stroke: "#000"}, svg);
+  el("line", {x1: m, y1: m, x2: m, y2: h - m, stroke: "#000"}, svg);
+  const step = Math.max(1, Math.ceil(xs.length / 20));
+  xs.forEach((x, i) => {
+    if (i % step) return;
+    const t = el("text", {x: m + (i + 0.5) * (w - 2 * m) / xs.length, y: h - m + 15, "text-anchor": "middle"}, svg);
+    t.textContent = x;
+  });
+  for (let i = 0; i <= 4; i++) {
+    const y = h - m - i * (h - 2 * m) / 4;
+    const t = el("text", {x: m - 5, y: y + 4, "text-anchor": "end"}, svg);
+    t.textContent = Math.round(ymax * i / 4).toLocaleString();
+    el("line", {x1: m, y1: y, x2: w - m, y2: y, stroke: "#eee"}, svg);
+  }
+  el("text", {x: w / 2, y: h - 5, "text-anchor": "middle"}, svg).textContent = xlabel;
+  el("text", {x: 12, y: h / 2, transform: "rotate(-90 12 " + h / 2 + ")", "text-anchor": "middle"}, svg).textContent = ylabel;
+}
+
+function drawHist() {
+  const w = 800, h = 350, m = 60;
+  const svg = el("svg", {width: w, height: h}, document.getElementById("hist"));
+  const vals = DATA.hist.values;
+  const ymax = Math.max(1, ...vals);
+  axes(svg, w, h, m, vals.map((_, i) => i), ymax, "coverage", "#" + DATA.hist.count);
+  const bw = (w - 2 * m) / vals.length;
+  vals.forEach((v, i) => {
+    const bh = v / ymax * (h - 2 * m);
+    const r = el("rect", {x: m + i * bw + 1, y: h - m - bh, width: Math.max(1, bw - 2), height: bh, fill: COLORS[0]}, svg);
+    el("title", {}, r).textContent = "coverage " + i + ": " + v.toLocaleString();
+  });
+}
+
+function drawGrowth() {
+  const w = 800, h = 350, m = 60;
+  const container = document.getElementById("growth");
+  const legend = document.getElementById("legend");
+  const hidden = new Set();
+  function render() {
+    container.innerHTML = "";
+    const svg = el("svg", {width: w, height: h}, container);
+    const series = DATA.growth.series.filter((_, j) => !hidden.has(j));
+    const ymax = Math.max(1, ...series.map(s => Math.max(...s.values)));
+    const xs = DATA.growth.labels;
+    axes(svg, w, h, m, xs, ymax, "groups", "#" + DATA.hist.count);
+    const dx = (w - 2 * m) / xs.length;
+    DATA.growth.series.forEach((s, j) => {
+      if (hidden.has(j)) return;
+      const pts = s.values.map((v, i) => [m + (i + 0.5) * dx, h - m - v / ymax * (h - 2 * m)]);
+      el("polyline", {points: pts.map(p => p.join(",")).join(" "), fill: "none", stroke: COLORS[j % COLORS.length], "stroke-width": 2}, svg);
+      pts.forEach((p, i) => {
+        const c = el("circle", {cx: p[0], cy: p[1], r: 3, fill: COLORS[j % COLORS.length]}, svg);
+        el("title", {}, c).textContent = s.name + ", " + xs[i] + " groups: " + s.values[i].toLocaleString();
+      });
+    });
+  }
+  DATA.growth.series.forEach((s, j) => {
+    const span = document.createElement("span");
+    span.style.color = COLORS[j % COLORS.length];
+    span.textContent = "■ " + s.name;
+    span.onclick = () => {
+      if (hidden.has(j)) hidden.delete(j); else hidden.add(j);
+      span.classList.toggle("off");
+      render();
+    };
+    legend.appendChild(span);
+  });
+  render();
+}
+
+document.getElementById("command").textContent = DATA.command;
+const stats = document.getElementById("stats");
+DATA.stats.forEach(([k, v]) => {
+  const row = stats.insertRow();
+  row.insertCell().textContent = k;
+  row.insertCell().textContent = v;
+});
+drawHist();
+drawGrowth();
+</script>
+</body>
+</html>
+"##;
+
+fn json_str_list(values: &[String]) -> String {
+    format!(
+        "[{}]",
+        values
+            .iter()
+            .map(|x| format!("\"{}\"", json_escape(x)))
+            .collect::<Vec<String>>()
+            .join(", ")
+    )
+}
+
+pub fn write_histgrowth_html<W: Write>(
+    hist: &Hist,
+    hist_aux: &HistAuxilliary,
+    growths: &[Vec<f64>],
+    graph_aux: &GraphAuxilliary,
+    n_groups: usize,
+    round: Rounding,
+    precision: usize,
+    out: &mut BufWriter<W>,
+) -> Result<(), std::io::Error> {
+    let stats = vec![
+        ("count type".to_string(), hist.count.to_string()),
+        ("nodes".to_string(), graph_aux.node_count.to_string()),
+        (
+            "edges (L-lines)".to_string(),
+            graph_aux.link_count.to_string(),
+        ),
+        ("total bp".to_string(), graph_aux.total_bp().to_string()),
+        (
+            "node length N50".to_string(),
+            graph_aux.node_len_n50().to_string(),
+        ),
+        (
+            "paths/walks".to_string(),
+            graph_aux.path_segments.len().to_string(),
+        ),
+        ("groups".to_string(), n_groups.to_string()),
+    ];
+
+    let series: Vec<String> = hist_aux
+        .coverage
+        .iter()
+        .zip(&hist_aux.quorum)
+        .zip(growths)
+        .map(|((c, q), g)| {
+            format!(
+                "{{\"name\": \"coverage >= {}, quorum >= {}\", \"values\": [{}]}}",
+                c.to_string(),
+                q.to_string(),
+                g.iter()
+                    .map(|x| round.format(*x, precision))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )
+        })
+        .collect();
+
+    let data = format!(
+        "{{\"command\": \"{}\", \"stats\": [{}], \"hist\": {{\"count\": \"{}\", \"values\": [{}]}}, \"growth\": {{\"labels\": [{}], \"series\": [{}]}}}}",
+        json_escape(&std::env::args().collect::<Vec<String>>().join(" ")),
+        stats
+            .iter()
+            .map(|(k, v)| json_str_list(&[k.clone(), v.clone()]))
+            .collect::<Vec<String>>()
+            .join(", "),
+        hist.count,
+        hist.coverage
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>()
+            .join(", "),
+        (1..=growths.get(0).map(|g| g.len()).unwrap_or(0))
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>()
+            .join(", "),
+        series.join(", ")
+    );
+
+    // make sure embedded data cannot terminate the script block
+    write!(
+        out,
+        "{}",
+        REPORT_TEMPLATE.replace("{{DATA}}", &data.replace("</", "<\\/"))
+    )?;
+    Ok(())
+}
@@ -1,17 +1,20 @@
 /* standard use */
 use std::collections::{HashMap, HashSet};
-use std::io::{BufRead, BufReader, Read};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
 use std::iter::FromIterator;
 use std::str::{self, FromStr};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
 /* crate use */
 use itertools::Itertools;
 use quick_csv::Csv;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use rayon::prelude::*;
 
 /* private use */
 use crate::abacus::*;
+use crate::cli::{parse_threshold_cli, RequireThreshold};
 use crate::graph::*;
 use crate::util::*;
 
@@ -81,6 +84,71 @@ pub fn parse_bed<R: Read>(data: &mut BufReader<R>) -> Vec<PathSegment> {
     res
 }
 
+pub fn parse_gff<R: Read>(data: &mut BufReader<R>) -> Result<Vec<PathSegment>, std::io::Error> {
+    // based on https://github.com/The-Sequence-Ontology/Specifications/blob/master/gff3.md
+    let mut res = Vec::new();
+
+    let reader = Csv::from_reader(data)
+        .delimiter(b'\t')
+        .flexible(true)
+        .has_header(false);
+    for (i, row) in reader.enumerate() {
+        let row = row.unwrap();
+        let cols: Vec<&[u8]> = row.bytes_columns().collect();
+        if cols.is_empty() || cols[0].is_empty() {
+            continue;
+        }
+        // "##FASTA" marks the beginning of an embedded sequence section
+        if cols[0].starts_with(b"##FASTA") {
+            break;
+        }
+        // skip directives ("##") and comments ("#")
+        if cols[0].starts_with(b"#") {
+            continue;
+        }
+        if cols.len() < 9 {
+            let msg = format!(
+                "error in line {}: GFF row must have 9 columns, but has {}",
+                i + 1,
+                cols.len()
+            );
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+        }
+        let parse_pos = |x: &[u8]| {
+            usize::from_str(str::from_utf8(x).unwrap()).map_err(|_| {
+                let msg = format!(
+                    "error in line {}: GFF start/end must be positive integers, but is '{}'",
+                    i + 1,
+                    String::from_utf8_lossy(x)
+                );
+                log::error!("{}", &msg);
+                std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+            })
+        };
+        let start = parse_pos(cols[3])?;
+        let end = parse_pos(cols[4])?;
+        if start == 0 || end < start {
+            let msg = format!(
+                "error in line {}: invalid GFF interval [{}, {}]",
+                i + 1,
+                start,
+                end
+            );
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+        }
+        let mut path_seg = PathSegment::from_str(str::from_utf8(cols[0]).unwrap());
+        // GFF coordinates are 1-based and inclusive, internally we use 0-based, end-exclusive
+        // coordinates as in BED
+        path_seg.start = Some(start - 1);
+        path_seg.end = Some(end);
+        res.push(path_seg);
+    }
+
+    Ok(res)
+}
+
 pub fn parse_groups<R: Read>(
     data: &mut BufReader<R>,
 ) -> Result<Vec<(PathSegment, String)>, std::io::Error> {
@@ -107,8 +175,10 @@ pub fn parse_groups<R: Read>(
     Ok(res)
 }
 
-pub fn parse_hist<R: Read>(data: &mut BufReader<R>) -> Result<Vec<usize>, std::io::Error> {
-    let mut table: HashMap<usize, usize> = HashMap::default();
+pub fn parse_group_rename<R: Read>(
+    data: &mut BufReader<R>,
+) -> Result<HashMap<String, String>, std::io::Error> {
+    let mut res: HashMap<String, String> = HashMap::default();
 
     let reader = Csv::from_reader(data)
         .delimiter(b'\t')
@@ -117,16 +187,230 @@ pub fn parse_hist<R: Read>(data: &mut BufReader<R>) -> Result<Vec<usize>, std::i
     for (i, row) in reader.enumerate() {
         let row = row.unwrap();
         let mut row_it = row.bytes_columns();
+        let old = str::from_utf8(row_it.next().unwrap()).unwrap().to_string();
+        if let Some(col) = row_it.next() {
+            let new = str::from_utf8(col).unwrap().to_string();
+            if let Some(prev) = res.insert(old.clone(), new.clone()) {
+                if prev != new {
+                    let msg = format!(
+                        "error in line {}: group {} cannot be renamed to both {} and {}",
+                        i + 1,
+                        &old,
+                        &prev,
+                        &new
+                    );
+                    log::error!("{}", &msg);
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+                }
+            }
+        } else {
+            let msg = format!("error in line {}: table must have two columns", i + 1);
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+        }
+    }
+
+    Ok(res)
+}
+
+pub fn parse_coverage_state<R: Read>(
+    data: &mut BufReader<R>,
+) -> Result<(Vec<String>, Vec<(Vec<u8>, CountSize)>), std::io::Error> {
+    // the first line lists the groups that have been counted, each following line gives a node
+    // and its coverage
+    let mut groups: Vec<String> = Vec::new();
+    let mut coverage: Vec<(Vec<u8>, CountSize)> = Vec::new();
+
+    let reader = Csv::from_reader(data)
+        .delimiter(b'\t')
+        .flexible(true)
+        .has_header(false);
+    for (i, row) in reader.enumerate() {
+        let row = row.unwrap();
+        let mut row_it = row.bytes_columns();
+        let first = row_it.next().unwrap();
+        if i == 0 {
+            if first != b"groups" {
+                let msg = "state file must start with a line listing the counted groups";
+                log::error!("{}", &msg);
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+            }
+            groups.extend(row_it.map(|x| str::from_utf8(x).unwrap().to_string()));
+        } else {
+            match row_it
+                .next()
+                .and_then(|x| str::from_utf8(x).ok()?.parse::<CountSize>().ok())
+            {
+                Some(c) => coverage.push((first.to_vec(), c)),
+                None => {
+                    let msg = format!(
+                        "error in line {}: expected node ID and coverage in state file",
+                        i + 1
+                    );
+                    log::error!("{}", &msg);
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+                }
+            }
+        }
+    }
+
+    Ok((groups, coverage))
+}
+
+pub fn parse_weighted_order<R: Read>(
+    data: &mut BufReader<R>,
+) -> Result<Option<Vec<(PathSegment, f64)>>, std::io::Error> {
+    // order lists may assign a weight to each path/group in an optional second column; lists
+    // without any weight (including BED files) are left to the coordinate parser
+    let mut res = Vec::new();
+    let mut weighted = false;
+    for (i, line) in data.lines().enumerate() {
+        let line = line?;
+        let line = line.trim_end_matches('\r');
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let cols: Vec<&str> = line.split('\t').collect();
+        match cols.len() {
+            1 => res.push((PathSegment::from_str(cols[0]), 1.0)),
+            2 => {
+                let w = f64::from_str(cols[1].trim())
+                    .ok()
+                    .filter(|x| x.is_finite() && *x >= 0.0)
+                    .ok_or_else(|| {
+                        let msg = format!(
+                            "error in line {}: weight must be a non-negative number, but is '{}'",
+                            i + 1,
+                            cols[1]
+                        );
+                        log::error!("{}", &msg);
+                        std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+                    })?;
+                res.push((PathSegment::from_str(cols[0]), w));
+                weighted = true;
+            }
+            n => {
+                if !weighted {
+                    return Ok(None);
+                }
+                let msg = format!(
+                    "error in line {}: weighted order list must have 1 or 2 columns, but has {}",
+                    i + 1,
+                    n
+                );
+                log::error!("{}", &msg);
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+            }
+        }
+    }
+    Ok(if weighted { Some(res) } else { None })
+}
+
+pub fn parse_paf_order<R: Read>(
+    data: &mut BufReader<R>,
+) -> Result<Vec<PathSegment>, std::io::Error> {
+    // PAF and GAF share the first 12 columns, with the query name in the 1st and the mapping
+    // quality in the 12th column
+    let mut queries: Vec<(String, usize)> = Vec::new();
+
+    let reader = Csv::from_reader(data)
+        .delimiter(b'\t')
+        .flexible(true)
+        .has_header(false);
+    for (i, row) in reader.enumerate() {
+        let row = row.unwrap();
+        let mut row_it = row.bytes_columns();
+        let query = str::from_utf8(row_it.next().unwrap()).unwrap().to_string();
+        if query.is_empty() || query.starts_with('#') {
+            continue;
+        }
+        if let Some(col) = row_it.nth(10) {
+            let mapq_str = str::from_utf8(col).unwrap();
+            let mapq = usize::from_str(mapq_str).map_err(|_| {
+                let msg = format!(
+                    "error in line {}: mapping quality must be integer, but is '{}'",
+                    i + 1,
+                    mapq_str
+                );
+                log::error!("{}", &msg);
+                std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+            })?;
+            queries.push((query, mapq));
+        } else {
+            let msg = format!(
+                "error in line {}: alignment record must have at least 12 columns",
+                i + 1
+            );
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+        }
+    }
+
+    // sort by descending mapping quality; sort is stable, so ties keep the order of the file
+    queries.sort_by(|(_, x), (_, y)| y.cmp(x));
+    let mut visited: HashSet<String> = HashSet::default();
+    Ok(queries
+        .into_iter()
+        .filter_map(|(q, _)| {
+            if visited.insert(q.clone()) {
+                Some(PathSegment::from_str(&q))
+            } else {
+                None
+            }
+        })
+        .collect())
+}
+
+pub fn parse_hist<R: Read>(
+    data: &mut BufReader<R>,
+) -> Result<(Option<CountType>, Vec<usize>), std::io::Error> {
+    let mut table: HashMap<usize, usize> = HashMap::default();
+    let mut count_type = None;
+    // columns holding coverage and counts; unless the header says otherwise, these are the first
+    // two, any further columns (e.g., cumulative counts) are ignored
+    let mut cov_col = 0;
+    let mut count_col = 1;
+
+    let reader = Csv::from_reader(data)
+        .delimiter(b'\t')
+        .flexible(true)
+        .has_header(false);
+    for (i, row) in reader.enumerate() {
+        let row = row.unwrap();
+        // skip comments, such as the command line and annotations written by the hist command
+        if row.bytes_columns().next().map(|x| x.starts_with(b"#")) == Some(true) {
+            continue;
+        }
+        let cols: Vec<&[u8]> = row.bytes_columns().collect();
         let cov;
         let count;
-        if let Some(cov_str) = row_it.next() {
+        if let Some(cov_str) = cols.get(cov_col) {
             if let Ok(val) = usize::from_str(&str::from_utf8(&cov_str).unwrap()) {
                 cov = val;
-            } else if i <= 1 {
+            } else if table.is_empty() {
                 log::info!(
                     "values in line {} are not integer, assuming this being a header line",
                     i
                 );
+                // header written by the hist command is of the form "coverage\t<count type>",
+                // possibly followed by further columns
+                for (k, name) in cols.iter().enumerate() {
+                    let name = str::from_utf8(name).unwrap().trim();
+                    if name == "traversals" {
+                        let msg = format!(
+                            "error in line {}: histogram reports numbers of traversals (as computed by hist --multiplicity) rather than numbers of covering groups, which is not supported here",
+                            i
+                        );
+                        log::error!("{}", &msg);
+                        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+                    } else if name == "coverage" {
+                        cov_col = k;
+                    } else if let (None, Ok(c)) = (count_type, CountType::from_str(name)) {
+                        log::info!("histogram header indicates count type \"{}\"", c);
+                        count_type = Some(c);
+                        count_col = k;
+                    }
+                }
                 continue;
             } else {
                 let msg = format!(
@@ -138,14 +422,18 @@ pub fn parse_hist<R: Read>(data: &mut BufReader<R>) -> Result<Vec<usize>, std::i
                 return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
             }
         } else {
-            let msg = format!("error in line {}: table must have two columns", i);
+            let msg = format!(
+                "error in line {}: table must have at least {} columns",
+                i,
+                usize::max(cov_col, count_col) + 1
+            );
             log::error!("{}", &msg);
             return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
         }
-        if let Some(count_str) = row_it.next() {
+        if let Some(count_str) = cols.get(count_col) {
             if let Ok(val) = usize::from_str(&str::from_utf8(&count_str).unwrap()) {
                 count = val;
-            } else if i == 0 {
+            } else if table.is_empty() {
                 log::info!(
                     "values in line {} are not integer, assuming this being a header line",
                     i
@@ -161,7 +449,11 @@ pub fn parse_hist<R: Read>(data: &mut BufReader<R>) -> Result<Vec<usize>, std::i
                 return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
             }
         } else {
-            let msg = format!("error in line {}: table must have two columns", i);
+            let msg = format!(
+                "error in line {}: table must have at least {} columns",
+                i,
+                usize::max(cov_col, count_col) + 1
+            );
             log::error!("{}", &msg);
             return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
         }
@@ -176,17 +468,24 @@ pub fn parse_hist<R: Read>(data: &mut BufReader<R>) -> Result<Vec<usize>, std::i
         }
     }
 
-    let max_cov = table.keys().max().unwrap();
+    let max_cov = match table.keys().max() {
+        Some(max_cov) => *max_cov,
+        None => {
+            let msg = "histogram has no entries";
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+        }
+    };
     log::info!("read counts for up to {}x coverage", &max_cov);
     let mut res = vec![0; max_cov + 1];
     table.into_iter().for_each(|(cov, count)| res[cov] = count);
 
-    Ok(res)
+    Ok((count_type, res))
 }
 
 pub fn parse_threshold_file<R: Read>(
     data: &mut BufReader<R>,
-) -> Result<Vec<Threshold>, std::io::Error> {
+) -> Result<Vec<(Threshold, Threshold)>, std::io::Error> {
     let mut res = Vec::new();
 
     let reader = Csv::from_reader(data)
@@ -196,91 +495,182 @@ pub fn parse_threshold_file<R: Read>(
     for (i, row) in reader.enumerate() {
         let row = row.unwrap();
         let mut row_it = row.bytes_columns();
-        if let Some(col) = row_it.next() {
-            let threshold_str = str::from_utf8(col).unwrap();
-            if let Ok(t) = usize::from_str(threshold_str) {
-                res.push(Threshold::Absolute(t));
-            } else if let Ok(t) = f64::from_str(threshold_str) {
-                res.push(Threshold::Relative(t));
-            } else {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    &format!(
-                        "threshold \"{}\" (line {}) is neither an integer nor a float",
-                        &threshold_str,
-                        i + 1
-                    )[..],
-                ));
+        let coverage_str = str::from_utf8(row_it.next().unwrap()).unwrap().trim();
+        if coverage_str.is_empty() || coverage_str.starts_with('#') {
+            continue;
+        }
+        let quorum_str = match row_it.next() {
+            Some(col) => str::from_utf8(col).unwrap().trim(),
+            None => {
+                let msg = format!(
+                    "error in line {}: threshold file must have two columns (coverage, quorum)",
+                    i + 1
+                );
+                log::error!("{}", &msg);
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
             }
+        };
+        // thresholds are parsed just like those given on the command line
+        let parse = |x: &str, require: RequireThreshold| {
+            parse_threshold_cli(x, require).map_err(|e| {
+                let msg = format!("error in line {}: {}", i + 1, e);
+                log::error!("{}", &msg);
+                std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+            })
+        };
+        let coverage = parse(coverage_str, RequireThreshold::Either)?;
+        let quorum = parse(quorum_str, RequireThreshold::Relative)?;
+        if coverage.len() != 1 || quorum.len() != 1 {
+            let msg = format!(
+                "error in line {}: each line must hold a single coverage/quorum pair",
+                i + 1
+            );
+            log::error!("{}", &msg);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
         }
+        res.push((coverage[0], quorum[0]));
     }
 
     Ok(res)
 }
 
-pub fn parse_walk_identifier<'a>(data: &'a [u8]) -> (PathSegment, &'a [u8]) {
-    let mut six_col: Vec<&str> = Vec::with_capacity(6);
+pub fn parse_walk_identifier<'a>(
+    data: &'a [u8],
+    line: usize,
+    decode_names: bool,
+) -> Result<(PathSegment, &'a [u8]), std::io::Error> {
+    let malformed = |reason: &str| {
+        let msg = format!(
+            "error in line {}: malformed walk record of sample {}: {}",
+            line,
+            String::from_utf8_lossy(data.split(|&x| x == b'\t').nth(1).unwrap_or(b"?")),
+            reason
+        );
+        log::error!("{}", &msg);
+        std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+    };
 
-    let mut it = data.iter();
-    let mut i = 0;
-    for _ in 0..6 {
-        let j = it.position(|x| x == &b'\t').unwrap();
-        six_col.push(&str::from_utf8(&data[i..i + j]).unwrap());
-        i += j + 1;
+    // W-line columns: W, sample ID, haplotype index, sequence ID, seq start, seq end, walk
+    let cols: Vec<&[u8]> = data.splitn(7, |&x| x == b'\t').collect();
+    if cols.len() < 5 {
+        return Err(malformed(&format!(
+            "expected 7 tab-separated columns, but found {}",
+            cols.len()
+        )));
+    }
+    let mut six_col: Vec<&str> = Vec::with_capacity(6);
+    for col in cols.iter().take(6) {
+        six_col.push(str::from_utf8(col).map_err(|_| malformed("column is not valid UTF-8"))?);
     }
 
-    let seq_start = match six_col[4] {
-        "*" => None,
-        a => Some(usize::from_str(a).unwrap()),
+    // seq start and end are optional: tools that omit them directly follow up with the walk
+    let walk_col = if cols[4].starts_with(b">") || cols[4].starts_with(b"<") {
+        log::debug!(
+            "walk record in line {} lacks seq start/end columns, treating them as \"*\"",
+            line
+        );
+        six_col.truncate(4);
+        six_col.push("*");
+        six_col.push("*");
+        4
+    } else if cols.len() < 7 {
+        return Err(malformed(&format!(
+            "expected 7 tab-separated columns, but found {}",
+            cols.len()
+        )));
+    } else {
+        6
     };
 
-    let seq_end = match six_col[5] {
-        "*" => None,
-        a => Some(usize::from_str(a).unwrap()),
+    let parse_coord = |a: &str, name: &str| match a {
+        "*" | "" => Ok(None),
+        a => usize::from_str(a).map(Some).map_err(|_| {
+            malformed(&format!(
+                "{} must be a non-negative integer or \"*\", but is \"{}\"",
+                name, a
+            ))
+        }),
     };
+    let seq_start = parse_coord(six_col[4], "seq start")?;
+    let seq_end = parse_coord(six_col[5], "seq end")?;
 
+    let decode = |x: &str| {
+        if decode_names {
+            String::from_utf8_lossy(&percent_decode(x.as_bytes())).to_string()
+        } else {
+            x.to_string()
+        }
+    };
     let path_seg = PathSegment::new(
-        six_col[1].to_string(),
+        decode(six_col[1]),
         six_col[2].to_string(),
-        six_col[3].to_string(),
+        decode(six_col[3]),
         seq_start,
         seq_end,
     );
 
-    (path_seg, &data[i..])
+    // the walk is the remainder of the line
+    let offset: usize = cols[..walk_col].iter().map(|x| x.len() + 1).sum();
+    Ok((path_seg, &data[offset..]))
 }
 
-pub fn parse_path_identifier<'a>(data: &'a [u8]) -> (PathSegment, &'a [u8]) {
+pub fn parse_path_identifier<'a>(data: &'a [u8], decode_names: bool) -> (PathSegment, &'a [u8]) {
     let mut iter = data.iter();
 
     let start = iter.position(|&x| x == b'\t').unwrap() + 1;
     let offset = iter.position(|&x| x == b'\t').unwrap();
-    let path_name = str::from_utf8(&data[start..start + offset]).unwrap();
+    let path_name = if decode_names {
+        String::from_utf8_lossy(&percent_decode(&data[start..start + offset])).to_string()
+    } else {
+        str::from_utf8(&data[start..start + offset])
+            .unwrap()
+            .to_string()
+    };
 
     (
-        PathSegment::from_str(path_name),
+        PathSegment::from_str(&path_name),
         &data[start + offset + 1..],
     )
 }
 
+fn lookup_node(
+    node: &[u8],
+    graph_aux: &GraphAuxilliary,
+    unknown_nodes: &AtomicUsize,
+) -> Option<ItemId> {
+    let sid = graph_aux.node_id(node).copied();
+    if sid.is_none() {
+        log::debug!(
+            "path/walk contains unknown node {{{}}}",
+            str::from_utf8(node).unwrap()
+        );
+        unknown_nodes.fetch_add(1, Ordering::Relaxed);
+    }
+    sid
+}
+
+// returns nothing if the walk traverses unknown nodes: leaving them out would join their
+// neighbors by edges that do not exist and shift the positions of all subsequent nodes
 fn parse_walk_seq_to_item_vec(
     data: &[u8],
     graph_aux: &GraphAuxilliary,
-) -> Vec<(ItemId, Orientation)> {
+    unknown_nodes: &AtomicUsize,
+) -> Option<Vec<(ItemId, Orientation)>> {
     // later codes assumes that data is non-empty...
     if data.is_empty() {
-        return Vec::new();
+        return Some(Vec::new());
     }
+    let unknown = AtomicUsize::new(0);
 
     // whatever the orientation of the first node is, will be used to split the sequence first;
     // this ensures that the first split results in an empty sequence at the beginning
     let s1 = Orientation::from_lg(data[0]);
     let s2 = s1.flip();
 
-    let mut it = data.iter();
-    let end = it
+    let end = data
+        .iter()
         .position(|x| x == &b'\t' || x == &b'\n' || x == &b'\r')
-        .unwrap();
+        .unwrap_or(data.len());
 
     log::debug!("parsing path sequences of size {}..", end);
 
@@ -295,20 +685,16 @@ fn parse_walk_seq_to_item_vec(
                 vec![]
             } else {
                 let i = x.iter().position(|z| &s2 == z).unwrap_or_else(|| x.len());
-                let sid = (
-                    *graph_aux.node2id.get(&x[..i]).expect(&format!(
-                        "walk contains unknown node {{{}}}'",
-                        str::from_utf8(&x[..i]).unwrap()
-                    )),
-                    s1,
-                );
+                let sid: Vec<(ItemId, Orientation)> = lookup_node(&x[..i], graph_aux, &unknown)
+                    .map(|sid| (sid, s1))
+                    .into_iter()
+                    .collect();
                 if i < x.len() {
                     // not nice... but Rust expects struct `std::iter::Once<(ItemIdSize, util::Orientation)>`
                     //
                     // this case can happen more frequently... hopefully it doesn't blow up the
                     // runtime
-                    [sid]
-                        .into_par_iter()
+                    sid.into_par_iter()
                         .chain(
                             x[i + 1..]
                                 .par_split(|y| &s2 == y)
@@ -316,27 +702,37 @@ fn parse_walk_seq_to_item_vec(
                                     if y.len() == 0 {
                                         vec![]
                                     } else {
-                                        vec![(
-                                            *graph_aux.node2id.get(&y[..]).expect(&format!(
-                                                "walk contains unknown node {{{}}}",
-                                                str::from_utf8(&y[..]).unwrap()
-                                            )),
-                                            s2,
-                                        )]
+                                        lookup_node(&y[..], graph_aux, &unknown)
+                                            .map(|sid| (sid, s2))
+                                            .into_iter()
+                                            .collect()
                                     }
                                 })
                                 .flatten(),
                         )
                         .collect()
                 } else {
-                    vec![sid]
+                    sid
                 }
             }
         })
         .flatten()
         .collect();
     log::debug!("..done");
-    sids
+    complete_item_vec(sids, unknown.into_inner(), unknown_nodes)
+}
+
+fn complete_item_vec(
+    sids: Vec<(ItemId, Orientation)>,
+    unknown: usize,
+    unknown_nodes: &AtomicUsize,
+) -> Option<Vec<(ItemId, Orientation)>> {
+    if unknown > 0 {
+        unknown_nodes.fetch_add(unknown, Ordering::Relaxed);
+        None
+    } else {
+        Some(sids)
+    }
 }
 
 fn parse_walk_seq_update_tables(
@@ -345,12 +741,14 @@ fn parse_walk_seq_update_tables(
     item_table: &mut ItemTable,
     exclude_table: Option<&mut ActiveTable>,
     num_path: usize,
+    unknown_nodes: &AtomicUsize,
 ) {
     // later codes assumes that data is non-empty...
     if data.is_empty() {
         return;
     }
 
+    let shards = item_table.shards;
     let items_ptr = Wrap(&mut item_table.items);
     let id_prefsum_ptr = Wrap(&mut item_table.id_prefsum);
 
@@ -360,10 +758,10 @@ fn parse_walk_seq_update_tables(
         .map(|x| Arc::new(Mutex::new(x)))
         .collect();
 
-    let mut it = data.iter();
-    let end = it
+    let end = data
+        .iter()
         .position(|x| x == &b'\t' || x == &b'\n' || x == &b'\r')
-        .unwrap();
+        .unwrap_or(data.len());
 
     log::debug!("parsing walk sequences of size {}..", end);
 
@@ -371,11 +769,11 @@ fn parse_walk_seq_update_tables(
     data[1..end]
         .par_split(|&x| x == b'>' || x == b'<')
         .for_each(|node| {
-            let sid = *graph_aux.node2id.get(&node[..]).expect(&format!(
-                "unknown node {}",
-                &str::from_utf8(node).unwrap()[..]
-            ));
-            let idx = (sid.0 as usize) % SIZE_T;
+            let sid = match lookup_node(&node[..], graph_aux, unknown_nodes) {
+                Some(sid) => sid,
+                None => return,
+            };
+            let idx = (sid.0 as usize) % shards;
             if let Ok(_) = mutex_vec[idx].lock() {
                 unsafe {
                     (*items_ptr.0)[idx].push(sid.0);
@@ -385,14 +783,14 @@ fn parse_walk_seq_update_tables(
         });
 
     // compute prefix sum
-    for i in 0..SIZE_T {
+    for i in 0..item_table.shards {
         item_table.id_prefsum[i][num_path + 1] += item_table.id_prefsum[i][num_path];
     }
 
     // is exclude table is given, we assume that all nodes of the path are excluded
     if let Some(ex) = exclude_table {
         log::error!("flagging nodes of path as excluded");
-        for i in 0..SIZE_T {
+        for i in 0..item_table.shards {
             for j in (item_table.id_prefsum[i][num_path] as usize)
                 ..(item_table.id_prefsum[i][num_path + 1] as usize)
             {
@@ -404,35 +802,32 @@ fn parse_walk_seq_update_tables(
     log::debug!("..done");
 }
 
+// returns nothing if the path traverses unknown nodes, see parse_walk_seq_to_item_vec
 fn parse_path_seq_to_item_vec(
     data: &[u8],
     graph_aux: &GraphAuxilliary,
-) -> Vec<(ItemId, Orientation)> {
-    let mut it = data.iter();
-    let end = it
+    unknown_nodes: &AtomicUsize,
+) -> Option<Vec<(ItemId, Orientation)>> {
+    let end = data
+        .iter()
         .position(|x| x == &b'\t' || x == &b'\n' || x == &b'\r')
-        .unwrap();
+        .unwrap_or(data.len());
 
     log::debug!("parsing path sequences of size {}..", end);
 
+    let unknown = AtomicUsize::new(0);
     let sids: Vec<(ItemId, Orientation)> = data[..end]
         .par_split(|&x| x == b',')
-        .map(|node| {
+        .filter_map(|node| {
             // Parallel
-            let sid = *graph_aux
-                .node2id
-                .get(&node[..node.len() - 1])
-                .expect(&format!(
-                    "unknown node {}",
-                    str::from_utf8(&node[..node.len() - 1]).unwrap()
-                ));
-            (sid, Orientation::from_pm(node[node.len() - 1]))
+            lookup_node(&node[..node.len() - 1], graph_aux, &unknown)
+                .map(|sid| (sid, Orientation::from_pm(node[node.len() - 1])))
         })
         .collect();
 
     log::debug!("..done");
 
-    sids
+    complete_item_vec(sids, unknown.into_inner(), unknown_nodes)
 }
 
 fn parse_path_seq_update_tables(
@@ -441,14 +836,16 @@ fn parse_path_seq_update_tables(
     item_table: &mut ItemTable,
     exclude_table: Option<&mut ActiveTable>,
     num_path: usize,
+    unknown_nodes: &AtomicUsize,
 ) {
-    let mut it = data.iter();
-    let end = it
+    let end = data
+        .iter()
         .position(|x| x == &b'\t' || x == &b'\n' || x == &b'\r')
-        .unwrap();
+        .unwrap_or(data.len());
 
     log::debug!("parsing path sequences of size {}..", end);
 
+    let shards = item_table.shards;
     let items_ptr = Wrap(&mut item_table.items);
     let id_prefsum_ptr = Wrap(&mut item_table.id_prefsum);
 
@@ -459,20 +856,17 @@ fn parse_path_seq_update_tables(
         .collect();
 
     data[..end].par_split(|&x| x == b',').for_each(|node| {
-        let sid = *graph_aux
-            .node2id
-            .get(&node[0..node.len() - 1])
-            .expect(&format!(
-                "unknown node {}",
-                &str::from_utf8(node).unwrap()[..]
-            ));
+        let sid = match lookup_node(&node[0..node.len() - 1], graph_aux, unknown_nodes) {
+            Some(sid) => sid,
+            None => return,
+        };
         let o = node[node.len() - 1];
         assert!(
             o == b'-' || o == b'+',
             "unknown orientation of segment {}",
             str::from_utf8(&node).unwrap()
         );
-        let idx = (sid.0 as usize) % SIZE_T;
+        let idx = (sid.0 as usize) % shards;
 
         if let Ok(_) = mutex_vec[idx].lock() {
             unsafe {
@@ -483,14 +877,14 @@ fn parse_path_seq_update_tables(
     });
 
     // compute prefix sum
-    for i in 0..SIZE_T {
+    for i in 0..item_table.shards {
         item_table.id_prefsum[i][num_path + 1] += item_table.id_prefsum[i][num_path];
     }
 
     // is exclude table is given, we assume that all nodes of the path are excluded
     if let Some(ex) = exclude_table {
         log::debug!("flagging nodes of path as excluded");
-        for i in 0..SIZE_T {
+        for i in 0..item_table.shards {
             for j in (item_table.id_prefsum[i][num_path] as usize)
                 ..(item_table.id_prefsum[i][num_path + 1] as usize)
             {
@@ -502,37 +896,183 @@ fn parse_path_seq_update_tables(
     log::debug!("..done");
 }
 
+// unknown nodes are skipped individually where neither edges nor positions are affected;
+// otherwise, the entire path/walk is skipped. In strict mode, unknown nodes are an error
+fn report_unknown_nodes(
+    unknown_nodes: &AtomicUsize,
+    skipped_paths: usize,
+    strict: bool,
+) -> Result<(), std::io::Error> {
+    let n = unknown_nodes.load(Ordering::Relaxed);
+    if n > 0 {
+        warn_or_fail(strict, &format!(
+            "found {} occurrences of nodes in paths/walks that are not defined by any S-line; skipped them, including {} paths/walks that traverse them",
+            n,
+            skipped_paths
+        ))?;
+    }
+    Ok(())
+}
+
+fn trim_line_end(buf: &mut Vec<u8>) {
+    // GFA files concatenated from chunks produced on different platforms can have mixed "\n" and
+    // "\r\n" line endings; removing them right after reading a line makes all downstream parsing
+    // agnostic of the line ending
+    while buf.last() == Some(&b'\n') || buf.last() == Some(&b'\r') {
+        buf.pop();
+    }
+}
+
+//
+// presents the lines of a paths file as GFA P/W-lines; lines that are not P/W-lines already are
+// expected to be of the form <name><tab><node list as in P-lines>
+//
+struct PathsFileReader<R: BufRead> {
+    data: R,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: BufRead> Read for PathsFileReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        if self.pos == self.buf.len() {
+            self.buf.clear();
+            self.pos = 0;
+            // skip empty lines and comments
+            while self.buf.is_empty() || self.buf[0] == b'#' {
+                self.buf.clear();
+                if self.data.read_until(b'\n', &mut self.buf)? == 0 {
+                    return Ok(0);
+                }
+                trim_line_end(&mut self.buf);
+            }
+            if !self.buf.starts_with(b"P\t") && !self.buf.starts_with(b"W\t") {
+                if !self.buf.contains(&b'\t') {
+                    let msg = format!(
+                        "path definition \"{}\" must be a GFA P/W-line or of the form <name><tab><node list>",
+                        String::from_utf8_lossy(&self.buf)
+                    );
+                    log::error!("{}", &msg);
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+                }
+                self.buf.splice(0..0, b"P\t".iter().copied());
+                if self.buf.iter().filter(|&&x| x == b'\t').count() == 2 {
+                    // P-lines end with an overlaps column, which is not needed here
+                    self.buf.extend_from_slice(b"\t*");
+                }
+            }
+            self.buf.push(b'\n');
+        }
+        let n = usize::min(out.len(), self.buf.len() - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+pub fn open_gfa(
+    gfa_file: &str,
+    paths_file: &str,
+) -> Result<BufReader<Box<dyn Read>>, std::io::Error> {
+    let data = std::fs::File::open(gfa_file)?;
+    if paths_file.is_empty() {
+        return Ok(buffered(Box::new(data)));
+    }
+    // paths are appended to the GFA, as if they were part of it; the extra newline takes care of
+    // GFA files that lack a final line break
+    let paths = PathsFileReader {
+        data: buffered(std::fs::File::open(paths_file)?),
+        buf: Vec::new(),
+        pos: 0,
+    };
+    Ok(buffered(Box::new(data.chain(&b"\n"[..]).chain(paths))))
+}
+
 pub fn parse_graph_aux<R: Read>(
     data: &mut BufReader<R>,
-    index_edges: bool,
+    options: &IndexOptions,
 ) -> Result<
     (
         HashMap<Vec<u8>, ItemId>,
         Vec<ItemIdSize>,
         Option<Vec<Vec<u8>>>,
+        Option<HashSet<Vec<u8>>>,
+        usize,
         Vec<PathSegment>,
+        Option<Vec<Option<f64>>>,
     ),
     std::io::Error,
 > {
+    let IndexOptions {
+        index_edges,
+        ignore_softmask,
+        decode_names,
+        normalize_ids,
+        missing_length,
+        strict,
+        ..
+    } = *options;
+    let strip_prefix = options.strip_prefix.as_bytes();
+    let depth_tag = options.depth_tag.as_bytes();
     // let's start
     // IMPORTANT: id must be > 0, otherwise counting procedure will produce errors
     let mut node_id = 1;
     let mut node2id: HashMap<Vec<u8>, ItemId> = HashMap::default();
     let mut edges: Option<Vec<Vec<u8>>> = if index_edges { Some(Vec::new()) } else { None };
+    // edges traversed by paths/walks, which need not be represented by L-lines
+    let mut path_edges: Option<HashSet<Vec<u8>>> = if index_edges {
+        Some(HashSet::default())
+    } else {
+        None
+    };
+    let mut link_count = 0;
     let mut path_segments: Vec<PathSegment> = Vec::new();
     let mut node_len: Vec<ItemIdSize> = Vec::new();
     // add empty element to node_len to make it in sync with node_id
     node_len.push(ItemIdSize::MAX);
+    let mut node_depth: Option<Vec<Option<f64>>> = if depth_tag.is_empty() {
+        None
+    } else {
+        Some(vec![None])
+    };
+
+    let mut normalized = 0;
+    // segments without sequence and LN tag, whose length is unknown
+    let mut missing = 0;
+    // segment IDs containing orientation characters cannot be referenced in walks, because
+    // walk sequences are split at these characters
+    let mut lg_node: Option<Vec<u8>> = None;
+    let mut has_walks = false;
 
     let mut buf = vec![];
+    let mut line = 0;
     while data.read_until(b'\n', &mut buf).unwrap_or(0) > 0 {
-        if buf[0] == b'S' {
+        line += 1;
+        trim_line_end(&mut buf);
+        if buf.is_empty() {
+            // skip empty lines
+        } else if buf[0] == b'S' {
             let mut iter = buf[2..].iter();
             let offset = iter.position(|&x| x == b'\t').unwrap();
-            if node2id
-                .insert(buf[2..offset + 2].to_vec(), ItemId(node_id))
-                .is_some()
-            {
+            if lg_node.is_none() && buf[2..offset + 2].iter().any(|&x| x == b'<' || x == b'>') {
+                lg_node = Some(buf[2..offset + 2].to_vec());
+            }
+            let node = if decode_names {
+                percent_decode(&buf[2..offset + 2])
+            } else {
+                (&buf[2..offset + 2]).into()
+            };
+            let node = node.strip_prefix(strip_prefix).unwrap_or(&node);
+            let node = if normalize_ids {
+                let res = normalize_id(node);
+                if res.len() < node.len() {
+                    normalized += 1;
+                }
+                res
+            } else {
+                node.into()
+            };
+            if node2id.insert(node.to_vec(), ItemId(node_id)).is_some() {
                 return Err(std::io::Error::new(
                     std::io::ErrorKind::InvalidData,
                     format!(
@@ -542,24 +1082,188 @@ pub fn parse_graph_aux<R: Read>(
                 ));
             }
             node_id += 1;
-            let offset = iter
+            let seq = iter.as_slice();
+            let offset = seq
+                .iter()
                 .position(|&x| x == b'\t' || x == b'\n' || x == b'\r')
-                .unwrap();
-            node_len.push(offset as ItemIdSize);
-        } else if index_edges && buf[0] == b'L' {
-            edges.as_mut().unwrap().push(buf.to_vec());
+                .unwrap_or(seq.len());
+            if &seq[..offset] == b"*" {
+                // the length of segments without sequence can only be learned from their LN tag
+                match seq[offset..]
+                    .split(|&x| x == b'\t')
+                    .find_map(|field| parse_numeric_tag(field, b"LN"))
+                {
+                    Some(l) => node_len.push(l as ItemIdSize),
+                    None if missing_length == MissingLength::Error => {
+                        let msg = format!(
+                            "error in line {}: segment {} has neither sequence nor LN tag, so its length is unknown; use --on-missing-length to skip such segments or to assume length 1",
+                            line,
+                            str::from_utf8(&node).unwrap()
+                        );
+                        log::error!("{}", &msg);
+                        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+                    }
+                    None => {
+                        missing += 1;
+                        node_len.push(if missing_length == MissingLength::Skip {
+                            0
+                        } else {
+                            1
+                        });
+                    }
+                }
+            } else if ignore_softmask {
+                // soft-masked (lowercase) bases do not contribute to the node length
+                node_len.push(
+                    seq[..offset]
+                        .iter()
+                        .filter(|x| !x.is_ascii_lowercase())
+                        .count() as ItemIdSize,
+                );
+            } else {
+                node_len.push(offset as ItemIdSize);
+            }
+            if let Some(depths) = node_depth.as_mut() {
+                depths.push(
+                    seq[offset..]
+                        .split(|&x| x == b'\t')
+                        .find_map(|field| parse_numeric_tag(field, depth_tag)),
+                );
+            }
+        } else if buf[0] == b'L' {
+            link_count += 1;
+            if index_edges {
+                edges.as_mut().unwrap().push(buf.to_vec());
+            }
         } else if buf[0] == b'P' {
-            let (path_seg, _) = parse_path_identifier(&buf);
+            let (path_seg, seq) = parse_path_identifier(&buf, decode_names);
+            if let Some(pe) = path_edges.as_mut() {
+                collect_path_edges(seq, false, pe);
+            }
             path_segments.push(path_seg);
         } else if buf[0] == b'W' {
-            let (path_seg, _) = parse_walk_identifier(&buf);
+            let (path_seg, seq) = parse_walk_identifier(&buf, line, decode_names)?;
+            if let Some(pe) = path_edges.as_mut() {
+                collect_path_edges(seq, true, pe);
+            }
             path_segments.push(path_seg);
+            has_walks = true;
         }
 
         buf.clear();
     }
 
-    Ok((node2id, node_len, edges, path_segments))
+    if let (Some(node), true) = (&lg_node, has_walks) {
+        let msg = format!(
+            "segment ID {} contains orientation character '<' or '>', which makes walks (W-lines) referring to it ambiguous; please rename the segment (e.g., percent-encode the character and use --decode-names)",
+            String::from_utf8_lossy(node)
+        );
+        log::error!("{}", &msg);
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+    }
+
+    if normalized > 0 {
+        warn_or_fail(
+            strict,
+            &format!(
+                "normalized IDs of {} segments by removing leading zeros",
+                normalized
+            ),
+        )?;
+    }
+
+    if missing > 0 {
+        warn_or_fail(
+            strict,
+            &format!(
+                "{} segments have neither sequence nor LN tag and are assumed to have length {}",
+                missing,
+                if missing_length == MissingLength::Skip {
+                    0
+                } else {
+                    1
+                }
+            ),
+        )?;
+    }
+
+    Ok((
+        node2id,
+        node_len,
+        edges,
+        path_edges,
+        link_count,
+        path_segments,
+        node_depth,
+    ))
+}
+
+// collects the pairs of consecutive steps of a path/walk sequence; segment IDs are resolved only
+// once all S-lines are known, so each pair is encoded as in walks, i.e., <orientation><segment>,
+// with both steps separated by a tab, and is stored in only one of its two orientations
+fn collect_path_edges(data: &[u8], is_walk: bool, edges: &mut HashSet<Vec<u8>>) {
+    let end = data
+        .iter()
+        .position(|x| x == &b'\t' || x == &b'\n' || x == &b'\r')
+        .unwrap_or(data.len());
+    let steps: Box<dyn Iterator<Item = (&[u8], u8)>> = if is_walk {
+        Box::new(
+            (0..end)
+                .filter(|&i| data[i] == b'>' || data[i] == b'<')
+                .chain(std::iter::once(end))
+                .tuple_windows()
+                .map(|(s, e)| (&data[s + 1..e], data[s])),
+        )
+    } else {
+        Box::new(
+            data[..end]
+                .split(|&x| x == b',')
+                .filter(|x| x.len() > 1)
+                .map(|x| {
+                    (
+                        &x[..x.len() - 1],
+                        Orientation::from_pm(x[x.len() - 1]).to_lg(),
+                    )
+                }),
+        )
+    };
+    let flip = |x: u8| if x == b'>' { b'<' } else { b'>' };
+    let mut key = Vec::new();
+    let mut flipped = Vec::new();
+    for ((u, o1), (v, o2)) in steps.tuple_windows() {
+        key.clear();
+        key.push(o1);
+        key.extend_from_slice(u);
+        key.push(b'\t');
+        key.push(o2);
+        key.extend_from_slice(v);
+        flipped.clear();
+        flipped.push(flip(o2));
+        flipped.extend_from_slice(v);
+        flipped.push(b'\t');
+        flipped.push(flip(o1));
+        flipped.extend_from_slice(u);
+        let k = if key <= flipped { &key } else { &flipped };
+        if !edges.contains(&k[..]) {
+            edges.insert(k.clone());
+        }
+    }
+}
+
+fn parse_numeric_tag(field: &[u8], tag: &[u8]) -> Option<f64> {
+    // optional fields are of the form <tag>:<type>:<value>; only integer and float types are
+    // considered
+    let n = tag.len();
+    if field.len() > n + 3
+        && field.starts_with(tag)
+        && field[n] == b':'
+        && (field[n + 1] == b'i' || field[n + 1] == b'f')
+        && field[n + 2] == b':'
+    {
+        str::from_utf8(&field[n + 3..]).ok()?.parse().ok()
+    } else {
+        None
+    }
 }
 
 fn build_subpath_map(path_segments: &Vec<PathSegment>) -> HashMap<String, Vec<(usize, usize)>> {
@@ -579,11 +1283,12 @@ fn build_subpath_map(path_segments: &Vec<PathSegment>) -> HashMap<String, Vec<(u
         let mut v: Vec<(usize, usize)> = coords.into_iter().collect();
         v.sort();
         let mut i = 1;
-        // remove overlaps
+        // merge overlapping and adjacent intervals so that each position belongs to at most one
+        // interval; mind that the merged interval must not shrink if the removed one is nested
         while i < v.len() {
             if v[i - 1].1 >= v[i].0 {
                 let x = v.remove(i);
-                v[i - 1].1 = x.1;
+                v[i - 1].1 = usize::max(v[i - 1].1, x.1);
             } else {
                 i += 1
             }
@@ -596,8 +1301,12 @@ pub fn parse_gfa_itemcount<R: Read>(
     data: &mut BufReader<R>,
     abacus_aux: &AbacusAuxilliary,
     graph_aux: &GraphAuxilliary,
-) -> (ItemTable, Option<ActiveTable>, Option<IntervalContainer>) {
-    let mut item_table = ItemTable::new(graph_aux.path_segments.len());
+) -> Result<(ItemTable, Option<ActiveTable>, Option<IntervalContainer>), std::io::Error> {
+    let mut item_table = ItemTable::new(
+        graph_aux.path_segments.len(),
+        graph_aux.number_of_items(&abacus_aux.count),
+        abacus_aux.shards,
+    );
 
     //
     // *only relevant for bps count in combination with subset option*
@@ -615,12 +1324,42 @@ pub fn parse_gfa_itemcount<R: Read>(
     //
     // this table stores information about excluded nodes *if* the exclude setting is used
     //
-    let mut exclude_table = abacus_aux.exclude_coords.as_ref().map(|_| {
-        ActiveTable::new(
+    let mut exclude_table = if abacus_aux.exclude_coords.is_some()
+        || abacus_aux.depth_range.is_some()
+        || abacus_aux.reference_coords.is_some()
+        || abacus_aux.relative_to_reference
+    {
+        Some(ActiveTable::new(
             graph_aux.number_of_items(&abacus_aux.count) + 1,
             abacus_aux.count == CountType::Bp,
-        )
-    });
+        ))
+    } else {
+        None
+    };
+
+    // nodes whose depth tag is missing or outside of the requested range are excluded entirely
+    if let (Some((min, max)), Some(depths), Some(ex)) = (
+        abacus_aux.depth_range,
+        graph_aux.node_depth.as_ref(),
+        exclude_table.as_mut(),
+    ) {
+        let mut c = 0;
+        for (i, d) in depths.iter().enumerate().skip(1) {
+            match d {
+                Some(x) if *x >= min && *x <= max => (),
+                _ => {
+                    ex.items[i] = true;
+                    c += 1;
+                }
+            }
+        }
+        log::info!(
+            "excluding {} nodes with depth outside of [{}, {}]",
+            c,
+            min,
+            max
+        );
+    }
 
     // build "include" lookup table
     let include_map = match &abacus_aux.include_coords {
@@ -634,25 +1373,52 @@ pub fn parse_gfa_itemcount<R: Read>(
         Some(coords) => build_subpath_map(coords),
     };
 
+    // build "reference" lookup table
+    let reference_map = match &abacus_aux.reference_coords {
+        None => HashMap::default(),
+        Some(coords) => build_subpath_map(coords),
+    };
+    let mut reference_paths: Vec<usize> = Vec::new();
+
+    // end positions of paths with subset/exclude coordinates, used to validate the coordinates
+    let mut path_ends: HashMap<String, usize> = HashMap::default();
+
     // reading GFA file searching for (P)aths and (W)alks
     let mut buf = vec![];
     let mut num_path = 0;
     let complete: Vec<(usize, usize)> = vec![(0, usize::MAX)];
+    let unknown_nodes = AtomicUsize::new(0);
+    let mut skipped_paths = 0;
 
+    let mut line = 0;
     while data.read_until(b'\n', &mut buf).unwrap_or(0) > 0 {
-        if buf[0] == b'P' || buf[0] == b'W' {
+        line += 1;
+        trim_line_end(&mut buf);
+        if !buf.is_empty() && (buf[0] == b'P' || buf[0] == b'W') {
             let (path_seg, buf_path_seg) = match buf[0] {
-                b'P' => parse_path_identifier(&buf),
-                b'W' => parse_walk_identifier(&buf),
+                b'P' => parse_path_identifier(&buf, graph_aux.decode_names),
+                b'W' => parse_walk_identifier(&buf, line, graph_aux.decode_names)?,
                 _ => unreachable!(),
             };
 
             log::debug!("processing path {}", &path_seg);
 
-            let include_coords = if abacus_aux.include_coords.is_none() {
+            // the reference path is processed like any other path, but its items are never
+            // counted; instead, they determine the items eligible for counting
+            let is_reference = abacus_aux.is_reference(&path_seg);
+            if is_reference {
+                reference_paths.push(num_path);
+            }
+            let (subset, subset_map) = if is_reference {
+                (&abacus_aux.reference_coords, &reference_map)
+            } else {
+                (&abacus_aux.include_coords, &include_map)
+            };
+
+            let include_coords = if subset.is_none() {
                 &complete[..]
             } else {
-                match include_map.get(&path_seg.id()) {
+                match subset_map.get(&path_seg.id()) {
                     None => &[],
                     Some(coords) => {
                         log::debug!(
@@ -681,16 +1447,24 @@ pub fn parse_gfa_itemcount<R: Read>(
             };
 
             let (start, end) = path_seg.coords().unwrap_or((0, usize::MAX));
+            let path_id = path_seg.id();
+            let has_coords = include_map.contains_key(&path_id)
+                || exclude_map.contains_key(&path_id)
+                || reference_map.contains_key(&path_id);
+            if has_coords && end != usize::MAX {
+                let e = path_ends.entry(path_id.clone()).or_insert(0);
+                *e = usize::max(*e, end);
+            }
 
             // do not process the path sequence if path is neither part of subset nor exclude
-            if abacus_aux.include_coords.is_some()
+            if subset.is_some()
                 && !intersects(include_coords, &(start, end))
                 && !intersects(exclude_coords, &(start, end))
             {
                 log::debug!("path {} does not intersect with subset coordinates {:?} nor with exclude coordinates {:?} and therefore is skipped from processing", &path_seg, &include_coords, &exclude_coords);
 
                 // update prefix sum
-                for i in 0..SIZE_T {
+                for i in 0..item_table.shards {
                     item_table.id_prefsum[i][num_path + 1] += item_table.id_prefsum[i][num_path];
                 }
 
@@ -700,8 +1474,7 @@ pub fn parse_gfa_itemcount<R: Read>(
             }
 
             if abacus_aux.count != CountType::Edge
-                && (abacus_aux.include_coords.is_none()
-                    || is_contained(include_coords, &(start, end)))
+                && (subset.is_none() || is_contained(include_coords, &(start, end)))
                 && (abacus_aux.exclude_coords.is_none()
                     || is_contained(exclude_coords, &(start, end)))
             {
@@ -719,6 +1492,7 @@ pub fn parse_gfa_itemcount<R: Read>(
                         &mut item_table,
                         ex,
                         num_path,
+                        &unknown_nodes,
                     ),
                     b'W' => parse_walk_seq_update_tables(
                         &buf_path_seg,
@@ -726,20 +1500,35 @@ pub fn parse_gfa_itemcount<R: Read>(
                         &mut item_table,
                         ex,
                         num_path,
+                        &unknown_nodes,
                     ),
                     _ => unreachable!(),
                 };
             } else {
                 let sids = match buf[0] {
-                    b'P' => parse_path_seq_to_item_vec(&buf_path_seg, &graph_aux),
-                    b'W' => parse_walk_seq_to_item_vec(&buf_path_seg, &graph_aux),
+                    b'P' => parse_path_seq_to_item_vec(&buf_path_seg, &graph_aux, &unknown_nodes),
+                    b'W' => parse_walk_seq_to_item_vec(&buf_path_seg, &graph_aux, &unknown_nodes),
                     _ => unreachable!(),
                 };
+                let skipped = sids.is_none();
+                let sids = sids.unwrap_or_else(|| {
+                    log::debug!("path {} traverses unknown nodes, skipping it", &path_seg);
+                    skipped_paths += 1;
+                    Vec::new()
+                });
+                if has_coords && !skipped {
+                    let len: usize = sids
+                        .iter()
+                        .map(|(sid, _)| graph_aux.node_len(sid) as usize)
+                        .sum();
+                    let e = path_ends.entry(path_id).or_insert(0);
+                    *e = usize::max(*e, start + len);
+                }
 
                 match abacus_aux.count {
-                    CountType::Node | CountType::Bp => update_tables(
+                    CountType::Node | CountType::Bp | CountType::Allele => update_tables(
                         &mut item_table,
-                        &mut subset_covered_bps.as_mut(),
+                        &mut subset_covered_bps.as_mut().filter(|_| !is_reference),
                         &mut exclude_table.as_mut(),
                         num_path,
                         &graph_aux,
@@ -764,7 +1553,354 @@ pub fn parse_gfa_itemcount<R: Read>(
         }
         buf.clear();
     }
-    (item_table, exclude_table, subset_covered_bps)
+    report_unknown_nodes(&unknown_nodes, skipped_paths, graph_aux.strict)?;
+    graph_aux.report_normalized_lookups()?;
+
+    // coordinates beyond the end of a path never match anything, which is likely a mistake
+    for (path_id, coords) in include_map
+        .iter()
+        .chain(exclude_map.iter())
+        .chain(reference_map.iter())
+    {
+        // intervals are sorted and merged, so the last one reaches furthest
+        if let (Some(path_end), Some(&(_, e))) = (path_ends.get(path_id), coords.last()) {
+            if e != usize::MAX && e > *path_end {
+                warn_or_fail(graph_aux.strict, &format!(
+                    "coordinates of path {} extend to position {}, which is {}bp beyond its end at position {}",
+                    path_id,
+                    e,
+                    e - path_end,
+                    path_end
+                ))?;
+            }
+        }
+    }
+
+    // items not visited by the reference path within its subset coordinates are excluded, or,
+    // if counting relative to the reference, those that are visited
+    if let Some(ex) = exclude_table
+        .as_mut()
+        .filter(|_| abacus_aux.reference_coords.is_some() || abacus_aux.relative_to_reference)
+    {
+        let mut on_reference = vec![false; ex.items.len()];
+        for k in reference_paths {
+            for i in 0..item_table.shards {
+                let start = item_table.id_prefsum[i][k] as usize;
+                let end = item_table.id_prefsum[i][k + 1] as usize;
+                for sid in &item_table.items[i][start..end] {
+                    on_reference[*sid as usize] = true;
+                }
+            }
+        }
+        let mut c = 0;
+        for (i, x) in on_reference.into_iter().enumerate().skip(1) {
+            if x == abacus_aux.relative_to_reference && !ex.items[i] {
+                ex.items[i] = true;
+                c += 1;
+            }
+        }
+        log::info!(
+            "excluding {} {}s {} by reference path{}",
+            c,
+            abacus_aux.count,
+            if abacus_aux.relative_to_reference {
+                "visited"
+            } else {
+                "not visited"
+            },
+            if abacus_aux.reference_coords.is_some() {
+                " within subset coordinates"
+            } else {
+                ""
+            }
+        );
+    }
+    Ok((item_table, exclude_table, subset_covered_bps))
+}
+
+pub fn parse_gfa_path_items<R: Read>(
+    data: &mut BufReader<R>,
+    graph_aux: &GraphAuxilliary,
+    count: CountType,
+) -> Result<Vec<Vec<ItemIdSize>>, std::io::Error> {
+    // items (nodes or edges) visited by each path/walk, in the order of path_segments
+    let mut res: Vec<Vec<ItemIdSize>> = Vec::new();
+    let unknown_nodes = AtomicUsize::new(0);
+    let mut skipped_paths = 0;
+
+    let mut buf = vec![];
+    let mut line = 0;
+    while data.read_until(b'\n', &mut buf).unwrap_or(0) > 0 {
+        line += 1;
+        trim_line_end(&mut buf);
+        if !buf.is_empty() && (buf[0] == b'P' || buf[0] == b'W') {
+            let sids = match buf[0] {
+                b'P' => {
+                    let (_, buf_path_seg) = parse_path_identifier(&buf, graph_aux.decode_names);
+                    parse_path_seq_to_item_vec(&buf_path_seg, &graph_aux, &unknown_nodes)
+                }
+                b'W' => {
+                    let (_, buf_path_seg) =
+                        parse_walk_identifier(&buf, line, graph_aux.decode_names)?;
+                    parse_walk_seq_to_item_vec(&buf_path_seg, &graph_aux, &unknown_nodes)
+                }
+                _ => unreachable!(),
+            };
+            // skipped paths remain in place, but without items
+            let sids = sids.unwrap_or_else(|| {
+                skipped_paths += 1;
+                Vec::new()
+            });
+            res.push(match count {
+                CountType::Edge => {
+                    let edge2id = graph_aux
+                        .edge2id
+                        .as_ref()
+                        .expect("counting edges requires edge2id map in GraphAuxilliary");
+                    sids.into_iter()
+                        .tuple_windows()
+                        .map(|((sid1, o1), (sid2, o2))| {
+                            edge2id[&graph_aux.edge_key(Edge::canonical(sid1, o1, sid2, o2))].0
+                        })
+                        .collect()
+                }
+                CountType::Node | CountType::Bp | CountType::Allele => {
+                    sids.into_iter().map(|(sid, _)| sid.0).collect()
+                }
+            });
+        }
+        buf.clear();
+    }
+    report_unknown_nodes(&unknown_nodes, skipped_paths, graph_aux.strict)?;
+    Ok(res)
+}
+
+pub fn synthetic_gfa(nodes: usize, paths: usize, seed: u64) -> Vec<u8> {
+    // the graph is a chain of sites, each of which is either a single node shared by all paths or
+    // a bubble of 2-3 alleles (mostly SNPs and short indels) of which each path takes one; the
+    // first allele is the most frequent, as in variation graphs built from a reference
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut res: Vec<u8> = Vec::new();
+    let mut sites: Vec<Vec<usize>> = Vec::new();
+    writeln!(res, "H\tVN:Z:1.0").unwrap();
+    let mut id = 1;
+    while id <= nodes {
+        let k = if rng.gen_bool(0.5) {
+            1
+        } else {
+            usize::min(rng.gen_range(2..=3), nodes - id + 1)
+        };
+        for j in 0..k {
+            let len = if k == 1 {
+                rng.gen_range(1..=100)
+            } else {
+                rng.gen_range(1..=5)
+            };
+            let seq: String = (0..len)
+                .map(|_| ['A', 'C', 'G', 'T'][rng.gen_range(0..4)])
+                .collect();
+            writeln!(res, "S\t{}\t{}", id + j, seq).unwrap();
+        }
+        sites.push((id..id + k).collect());
+        id += k;
+    }
+    for (a, b) in sites.iter().tuple_windows() {
+        for u in a {
+            for v in b {
+                writeln!(res, "L\t{}\t+\t{}\t+\t0M", u, v).unwrap();
+            }
+        }
+    }
+    for i in 0..paths {
+        let steps: Vec<String> = sites
+            .iter()
+            .map(|site| {
+                let j = if site.len() == 1 || rng.gen_bool(0.7) {
+                    0
+                } else {
+                    rng.gen_range(1..site.len())
+                };
+                format!("{}+", site[j])
+            })
+            .collect();
+        writeln!(
+            res,
+            "P\tsample{}#{}#chr1\t{}\t*",
+            i / 2,
+            i % 2 + 1,
+            steps.join(",")
+        )
+        .unwrap();
+    }
+    res
+}
+
+fn retained_runs<'a>(
+    steps: &[(&'a [u8], Option<ItemId>)],
+    keep: &[bool],
+    graph_aux: &GraphAuxilliary,
+) -> Vec<(usize, usize, Vec<&'a [u8]>)> {
+    // maximal stretches of consecutive steps through retained nodes, along with their start and
+    // end positions relative to the start of the path/walk
+    let mut res = Vec::new();
+    let mut pos = 0;
+    let mut run: Vec<&[u8]> = Vec::new();
+    let mut start = 0;
+    for &(step, sid) in steps {
+        match sid {
+            Some(sid) if keep[sid.0 as usize] => {
+                if run.is_empty() {
+                    start = pos;
+                }
+                run.push(step);
+            }
+            _ => {
+                if !run.is_empty() {
+                    res.push((start, pos, std::mem::take(&mut run)));
+                }
+            }
+        }
+        pos += sid
+            .map(|x| graph_aux.node_len_ary[x.0 as usize])
+            .unwrap_or(0) as usize;
+    }
+    if !run.is_empty() {
+        res.push((start, pos, run));
+    }
+    res
+}
+
+pub fn write_subgraph<R: Read, W: Write>(
+    data: &mut BufReader<R>,
+    graph_aux: &GraphAuxilliary,
+    keep: &[bool],
+    out: &mut BufWriter<W>,
+) -> Result<(), std::io::Error> {
+    let is_kept = |node: &[u8]| {
+        graph_aux
+            .node_id(node)
+            .map(|x| keep[x.0 as usize])
+            .unwrap_or(false)
+    };
+
+    let mut c_nodes = 0;
+    let mut c_links = 0;
+    let mut c_paths = 0;
+    let mut buf = vec![];
+    let mut line = 0;
+    while data.read_until(b'\n', &mut buf).unwrap_or(0) > 0 {
+        line += 1;
+        trim_line_end(&mut buf);
+        let cols: Vec<&[u8]> = buf.split(|&x| x == b'\t').collect();
+        match buf.first() {
+            Some(b'H') => {
+                out.write_all(&buf)?;
+                writeln!(out)?;
+            }
+            Some(b'S') if cols.len() > 2 => {
+                if is_kept(cols[1]) {
+                    out.write_all(&buf)?;
+                    writeln!(out)?;
+                    c_nodes += 1;
+                }
+            }
+            Some(b'L') if cols.len() > 4 => {
+                if is_kept(cols[1]) && is_kept(cols[3]) {
+                    out.write_all(&buf)?;
+                    writeln!(out)?;
+                    c_links += 1;
+                }
+            }
+            Some(b'P') if cols.len() > 3 => {
+                let steps: Vec<(&[u8], Option<ItemId>)> = cols[2]
+                    .split(|&x| x == b',')
+                    .map(|x| (x, graph_aux.node_id(&x[..x.len().max(1) - 1]).copied()))
+                    .collect();
+                let runs = retained_runs(&steps, keep, graph_aux);
+                if runs.len() == 1 && runs[0].2.len() == steps.len() {
+                    // path is retained in its entirety
+                    out.write_all(&buf)?;
+                    writeln!(out)?;
+                    c_paths += 1;
+                    buf.clear();
+                    continue;
+                }
+                // each stretch becomes a path of its own whose name carries its coordinates
+                let name = str::from_utf8(cols[1]).unwrap();
+                let (base, offset) = match PathSegment::from_str(name).coords() {
+                    Some((start, _)) => (&name[..name.rfind(':').unwrap()], start),
+                    None => (name, 0),
+                };
+                for (start, end, run) in runs {
+                    writeln!(
+                        out,
+                        "P\t{}:{}-{}\t{}\t*",
+                        base,
+                        offset + start,
+                        offset + end,
+                        run.iter()
+                            .map(|x| str::from_utf8(x).unwrap())
+                            .collect::<Vec<&str>>()
+                            .join(",")
+                    )?;
+                    c_paths += 1;
+                }
+            }
+            Some(b'W') => {
+                let (_, walk) = parse_walk_identifier(&buf, line, graph_aux.decode_names)?;
+                let walk = walk.split(|&x| x == b'\t').next().unwrap();
+                let mut steps: Vec<(&[u8], Option<ItemId>)> = Vec::new();
+                let mut i = 0;
+                while i < walk.len() {
+                    let j = walk[i + 1..]
+                        .iter()
+                        .position(|&x| x == b'>' || x == b'<')
+                        .map(|x| x + i + 1)
+                        .unwrap_or(walk.len());
+                    steps.push((&walk[i..j], graph_aux.node_id(&walk[i + 1..j]).copied()));
+                    i = j;
+                }
+                let runs = retained_runs(&steps, keep, graph_aux);
+                if runs.len() == 1 && runs[0].2.len() == steps.len() {
+                    out.write_all(&buf)?;
+                    writeln!(out)?;
+                    c_paths += 1;
+                    buf.clear();
+                    continue;
+                }
+                // each stretch becomes a walk of its own with adjusted start and end positions
+                let offset = str::from_utf8(cols[4])
+                    .ok()
+                    .and_then(|x| usize::from_str(x).ok())
+                    .unwrap_or(0);
+                for (start, end, run) in runs {
+                    writeln!(
+                        out,
+                        "W\t{}\t{}\t{}\t{}\t{}\t{}",
+                        str::from_utf8(cols[1]).unwrap(),
+                        str::from_utf8(cols[2]).unwrap(),
+                        str::from_utf8(cols[3]).unwrap(),
+                        offset + start,
+                        offset + end,
+                        run.iter()
+                            .map(|x| str::from_utf8(x).unwrap())
+                            .collect::<Vec<&str>>()
+                            .join("")
+                    )?;
+                    c_paths += 1;
+                }
+            }
+            _ => (),
+        }
+        buf.clear();
+    }
+    log::info!(
+        "subgraph has {} nodes, {} L-lines, and {} paths/walks",
+        c_nodes,
+        c_links,
+        c_paths
+    );
+    Ok(())
 }
 
 fn update_tables(
@@ -852,7 +1988,7 @@ fn update_tables(
 
             // only count nodes that are completely contained in "include" coords
             if subset_covered_bps.is_some() || b - a == l {
-                let idx = (sid.0 as usize) % SIZE_T;
+                let idx = (sid.0 as usize) % item_table.shards;
                 item_table.items[idx].push(sid.0);
                 item_table.id_prefsum[idx][num_path + 1] += 1;
                 if let Some(int) = subset_covered_bps.as_mut() {
@@ -910,7 +2046,7 @@ fn update_tables(
     );
 
     // Compute prefix sum
-    for i in 0..SIZE_T {
+    for i in 0..item_table.shards {
         item_table.id_prefsum[i][num_path + 1] += item_table.id_prefsum[i][num_path];
     }
     log::debug!("..done");
@@ -937,20 +2073,27 @@ fn update_tables_edgecount(
 
     log::debug!("checking inclusion/exclusion criteria on {} nodes, inserting successful candidates to corresponding data structures..", path.len());
 
+    //
+    // edge count handling: analogous to nodes, an edge is only counted (or excluded) if *both*
+    // of its endpoint nodes are completely covered by subset (or exclude) coordinates; intervals
+    // are merged beforehand, so both nodes must lie within the same interval
+    //
     for ((sid1, o1), (sid2, o2)) in path.into_iter().tuple_windows() {
+        // the edge spans from the start of the first to the end of the second node
+        let start = p - graph_aux.node_len(&sid1) as usize;
+        let l = graph_aux.node_len(&sid2) as usize;
+
         // update current pointer in include_coords list
-        while i < include_coords.len() && include_coords[i].1 <= p {
+        while i < include_coords.len() && include_coords[i].1 <= start {
             i += 1;
         }
 
         // update current pointer in exclude_coords list
-        while j < exclude_coords.len() && exclude_coords[j].1 <= p {
+        while j < exclude_coords.len() && exclude_coords[j].1 <= start {
             j += 1;
         }
 
-        let l = graph_aux.node_len(&sid2) as usize;
-
-        let e = Edge::canonical(sid1, o1, sid2, o2);
+        let e = graph_aux.edge_key(Edge::canonical(sid1, o1, sid2, o2));
         let eid = graph_aux
             .edge2id
             .as_ref()
@@ -965,13 +2108,18 @@ fn update_tables_edgecount(
                     "No"
                 }
             ));
-        // check if the current position fits within active segment
-        if i < include_coords.len() && include_coords[i].0 < p + l {
-            let idx = (eid.0 as usize) % SIZE_T;
+        // check if both endpoints fit within active segment
+        if i < include_coords.len() && include_coords[i].0 <= start && include_coords[i].1 >= p + l
+        {
+            let idx = (eid.0 as usize) % item_table.shards;
             item_table.items[idx].push(eid.0);
             item_table.id_prefsum[idx][num_path + 1] += 1;
         }
-        if exclude_table.is_some() && j < exclude_coords.len() && exclude_coords[j].0 < p + l {
+        if exclude_table.is_some()
+            && j < exclude_coords.len()
+            && exclude_coords[j].0 <= start
+            && exclude_coords[j].1 >= p + l
+        {
             exclude_table.as_mut().unwrap().activate(eid);
         } else if i >= include_coords.len() && j >= exclude_coords.len() {
             // terminate parse if all "include" and "exclude" coords are processed
@@ -980,8 +2128,122 @@ fn update_tables_edgecount(
         p += l;
     }
     // Compute prefix sum
-    for i in 0..SIZE_T {
+    for i in 0..item_table.shards {
         item_table.id_prefsum[i][num_path + 1] += item_table.id_prefsum[i][num_path];
     }
     log::debug!("..done");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph_aux(gfa: &[u8], index_edges: bool) -> GraphAuxilliary {
+        GraphAuxilliary::from_gfa(
+            &mut BufReader::new(gfa),
+            &IndexOptions {
+                index_edges,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_unknown_nodes_skip_path() {
+        let gfa = b"S\t1\tAAA\nS\t2\tCC\nS\t3\tG\nL\t1\t+\t2\t+\t0M\nL\t2\t+\t3\t+\t0M\nP\tp1\t1+,2+\t*\nP\tp2\t1+,9+,3+\t*\nW\ts\t0\tc\t0\t4\t>2<9>3\n";
+        let graph_aux = graph_aux(gfa, true);
+
+        // no edge is made up between the neighbors of the unknown node
+        let n = |x: &[u8]| *graph_aux.node_id(x).unwrap();
+        let mut expected = HashSet::default();
+        expected.insert(Edge::canonical(
+            n(b"1"),
+            Orientation::Forward,
+            n(b"2"),
+            Orientation::Forward,
+        ));
+        assert_eq!(graph_aux.path_edges, Some(expected));
+
+        // paths traversing unknown nodes keep their place, but have no items
+        let items =
+            parse_gfa_path_items(&mut BufReader::new(&gfa[..]), &graph_aux, CountType::Node)
+                .unwrap();
+        assert_eq!(items, vec![vec![n(b"1").0, n(b"2").0], vec![], vec![]]);
+
+        // in strict mode, unknown nodes are an error
+        let mut graph_aux = graph_aux;
+        graph_aux.strict = true;
+        assert!(
+            parse_gfa_path_items(&mut BufReader::new(&gfa[..]), &graph_aux, CountType::Node)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_reject_traversal_hist() {
+        let hist = b"# coverage: number of traversals\ntraversals\tnode\n0\t1\n1\t2\n3\t1\n";
+        assert!(parse_hist(&mut BufReader::new(&hist[..])).is_err());
+        let hist = b"coverage\tnode\n0\t1\n1\t2\n3\t1\n";
+        assert_eq!(
+            parse_hist(&mut BufReader::new(&hist[..])).unwrap(),
+            (Some(CountType::Node), vec![1, 2, 0, 1])
+        );
+    }
+
+    #[test]
+    fn test_reject_empty_hist() {
+        let hist = b"panacus\tcount\tnode\ncoverage\tnode\n";
+        assert!(parse_hist(&mut BufReader::new(&hist[..])).is_err());
+        // a histogram without any covered countable yields no estimates
+        let hist = crate::hist::Hist {
+            count: CountType::Node,
+            coverage: vec![3],
+        };
+        let mut out = std::io::BufWriter::new(Vec::new());
+        assert!(hist.estimate_to_tsv(1, &mut out).is_err());
+    }
+
+    #[test]
+    fn test_merge_overlapping_intervals() {
+        let bed =
+            b"A#1#c\t0\t5\nA#1#c\t3\t8\nA#1#c\t4\t6\nA#1#c\t10\t12\nA#1#c\t12\t15\nB#1#c\t2\t4\n";
+        let coords = build_subpath_map(&parse_bed(&mut BufReader::new(&bed[..])));
+        // overlapping, nested, and adjacent intervals are merged
+        assert_eq!(coords["A#1#c"], vec![(0, 8), (10, 15)]);
+        assert_eq!(coords["B#1#c"], vec![(2, 4)]);
+    }
+
+    #[test]
+    fn test_gff_rows() {
+        let gff = b"##gff-version 3\nA#1#c\t.\tgene\t3\t8\t.\t+\t.\tID=g\n";
+        let coords = parse_gff(&mut BufReader::new(&gff[..])).unwrap();
+        assert_eq!((coords[0].start, coords[0].end), (Some(2), Some(8)));
+        // rows with fewer than 9 columns are rejected, even if start and end are given
+        let gff = b"A#1#c\t.\tgene\t3\t8\n";
+        assert!(parse_gff(&mut BufReader::new(&gff[..])).is_err());
+    }
+
+    #[test]
+    fn test_dangling_links() {
+        let gfa =
+            b"S\t1\tAAA\nS\t2\tCC\nL\t1\t+\t2\t+\t0M\nL\t2\t+\t9\t-\t0M\nP\tA#1#c\t1+,2+\t*\n";
+        let graph_aux = graph_aux(gfa, true);
+        assert_eq!(graph_aux.edge_count, 1);
+        assert_eq!(graph_aux.dangling_links, vec![">2<9".to_string()]);
+        let mut out = std::io::BufWriter::new(Vec::new());
+        graph_aux.write_edge_audit(&mut out).unwrap();
+        let audit = String::from_utf8(out.into_inner().unwrap()).unwrap();
+        assert_eq!(audit, "category\tedge\ndangling\t>2<9\n");
+        // in strict mode, dangling links are an error
+        assert!(GraphAuxilliary::from_gfa(
+            &mut BufReader::new(&gfa[..]),
+            &IndexOptions {
+                index_edges: true,
+                strict: true,
+                ..Default::default()
+            },
+        )
+        .is_err());
+    }
+}
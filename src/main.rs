@@ -7,6 +7,7 @@ mod abacus;
 mod cli;
 mod graph;
 mod hist;
+mod html;
 mod io;
 mod util;
 
@@ -18,10 +19,10 @@ fn main() -> Result<(), std::io::Error> {
     let mut out = std::io::BufWriter::new(std::io::stdout());
 
     // read parameters and store them in memory
-    let params = cli::read_params();
+    let (params, config) = cli::read_params();
 
     // ride on!
-    cli::run(params, &mut out)?;
+    cli::run(params, config, &mut out)?;
 
     // clean up & close down
     out.flush()?;
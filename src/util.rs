@@ -1,9 +1,13 @@
 /* standard use */
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 
 /* external use */
+use flate2::read::MultiGzDecoder;
 use strum_macros::{EnumString, EnumVariantNames};
 
 /* internal use */
@@ -16,15 +20,60 @@ pub type ItemIdSize = u32;
 pub type CountSize = u32;
 pub type GroupSize = u16;
 
+//
+// bounds on the number of shards of ItemTable and targeted number of items per shard
+//
 pub const SIZE_T: usize = 1024;
+pub const MIN_SHARDS: usize = 16;
+pub const ITEMS_PER_SHARD: usize = 1024;
+
+//
+// strict mode promotes warnings to errors
+//
+pub fn warn_or_fail(strict: bool, msg: &str) -> Result<(), std::io::Error> {
+    if strict {
+        log::error!("{} (strict mode)", msg);
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("{} (strict mode)", msg),
+        ))
+    } else {
+        log::warn!("{}", msg);
+        Ok(())
+    }
+}
+
+//
+// capacity of the buffered readers of input files
+//
+static BUFFER_SIZE: AtomicUsize = AtomicUsize::new(8 * 1024);
+
+pub fn set_buffer_size(size: usize) {
+    BUFFER_SIZE.store(size, AtomicOrdering::Relaxed);
+}
+
+pub fn buffered<R: Read>(data: R) -> BufReader<R> {
+    BufReader::with_capacity(BUFFER_SIZE.load(AtomicOrdering::Relaxed), data)
+}
+
+pub fn open_file(file_name: &str) -> Result<BufReader<Box<dyn Read>>, std::io::Error> {
+    // gzip-compressed files are recognized by their magic number, irrespective of their name
+    let mut data = buffered(std::fs::File::open(file_name)?);
+    let is_gzip = data.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+    Ok(if is_gzip {
+        log::debug!("decompressing {}", file_name);
+        buffered(Box::new(MultiGzDecoder::new(data)))
+    } else {
+        buffered(Box::new(data))
+    })
+}
+
 pub struct Wrap<T>(pub *mut T);
 unsafe impl Sync for Wrap<Vec<usize>> {}
 unsafe impl Sync for Wrap<Vec<u64>> {}
 unsafe impl Sync for Wrap<Vec<u32>> {}
 unsafe impl Sync for Wrap<Vec<u16>> {}
-unsafe impl Sync for Wrap<[Vec<u32>; SIZE_T]> {}
 unsafe impl Sync for Wrap<Vec<Vec<u32>>> {}
-unsafe impl Sync for Wrap<[Vec<u64>; SIZE_T]> {}
 unsafe impl Sync for Wrap<Vec<Vec<u64>>> {}
 
 #[derive(Debug, Clone, Copy, PartialEq, EnumString, EnumVariantNames)]
@@ -33,6 +82,19 @@ pub enum CountType {
     Node,
     Bp,
     Edge,
+    Allele,
+}
+
+impl CountType {
+    // whether counting requires the edge index of GraphAuxilliary: edges are the counted items
+    // themselves, and alleles are identified by the bubbles the edges form; the index is
+    // expensive on large graphs, so it must not be built for any other count type
+    pub fn requires_edge_index(&self) -> bool {
+        match self {
+            CountType::Edge | CountType::Allele => true,
+            CountType::Node | CountType::Bp => false,
+        }
+    }
 }
 
 impl fmt::Display for CountType {
@@ -44,23 +106,125 @@ impl fmt::Display for CountType {
                 CountType::Node => "node",
                 CountType::Edge => "edge",
                 CountType::Bp => "bp",
+                CountType::Allele => "allele",
             }
         )
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum GrowthCurve {
+    Union,
+    Core,
+}
+
+impl GrowthCurve {
+    // coverage and quorum thresholds that produce the curve
+    pub fn thresholds(&self) -> (Threshold, Threshold) {
+        match self {
+            GrowthCurve::Union => (Threshold::Absolute(1), Threshold::Relative(0.0)),
+            GrowthCurve::Core => (Threshold::Absolute(1), Threshold::Relative(1.0)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "kebab-case")]
+pub enum FitModel {
+    TettelinCore,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum OutputFormat {
+    Table,
+    Html,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum Denominator {
+    Paths,
+    Graph,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum Direction {
+    Grow,
+    Decay,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum MissingLength {
+    Error,
+    Skip,
+    One,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum HotspotScore {
+    Variance,
+    Entropy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumString, EnumVariantNames)]
+#[strum(serialize_all = "lowercase")]
+pub enum Rounding {
+    Floor,
+    Round,
+    Ceil,
+    None,
+}
+
+impl Rounding {
+    // values are reported with decimal places (as many as given by precision) only if they are
+    // not rounded to integers
+    pub fn format(&self, x: f64, precision: usize) -> String {
+        match self {
+            Rounding::Floor => x.floor().to_string(),
+            Rounding::Round => x.round().to_string(),
+            Rounding::Ceil => x.ceil().to_string(),
+            Rounding::None => format!("{:.*}", precision, x),
+        }
+    }
+}
+
 pub struct ItemTable {
-    pub items: [Vec<ItemIdSize>; SIZE_T],
-    pub id_prefsum: [Vec<ItemIdSize>; SIZE_T],
+    pub shards: usize,
+    pub items: Vec<Vec<ItemIdSize>>,
+    pub id_prefsum: Vec<Vec<ItemIdSize>>,
 }
 
 impl ItemTable {
-    pub fn new(num_walks_paths: usize) -> Self {
+    pub fn new(num_walks_paths: usize, num_items: usize, shards: Option<usize>) -> Self {
+        let shards = shards.unwrap_or_else(|| Self::shards(num_items));
+        log::debug!(
+            "distributing {} items across {} shards of item table",
+            num_items,
+            shards
+        );
         Self {
-            items: [(); SIZE_T].map(|_| vec![]),
-            id_prefsum: [(); SIZE_T].map(|_| vec![0; num_walks_paths + 1]),
+            shards: shards,
+            items: vec![Vec::new(); shards],
+            id_prefsum: vec![vec![0; num_walks_paths + 1]; shards],
         }
     }
+
+    pub fn shards(num_items: usize) -> usize {
+        // the number of shards is adapted to the number of items so that shards remain balanced;
+        // it is bounded from above, because each shard keeps a prefix sum over all paths/walks
+        usize::min(
+            SIZE_T,
+            usize::max(
+                MIN_SHARDS,
+                (num_items / ITEMS_PER_SHARD).next_power_of_two(),
+            ),
+        )
+    }
 }
 
 pub struct ActiveTable {
@@ -290,10 +454,209 @@ impl Threshold {
     }
 }
 
+// counts of a run that are reported as metrics, along with their descriptions
+const METRICS: [(&str, &str); 5] = [
+    ("nodes", "Number of nodes in the graph"),
+    ("edges", "Number of edges in the graph"),
+    ("paths", "Number of paths/walks in the graph"),
+    ("groups", "Number of path groups"),
+    ("countables", "Number of countable items in the abacus"),
+];
+
+//
+// structured record of a run, written as JSON
+//
+pub struct RunStats {
+    entries: Vec<(String, String)>,
+    timings: Vec<(String, f64)>,
+}
+
+impl RunStats {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            timings: Vec::new(),
+        }
+    }
+
+    pub fn add_str(&mut self, key: &str, value: &str) {
+        self.entries
+            .push((key.to_string(), format!("\"{}\"", json_escape(value))));
+    }
+
+    pub fn add_num<T: fmt::Display>(&mut self, key: &str, value: T) {
+        self.entries.push((key.to_string(), value.to_string()));
+    }
+
+    pub fn add_str_list(&mut self, key: &str, values: &[String]) {
+        self.entries.push((
+            key.to_string(),
+            format!(
+                "[{}]",
+                values
+                    .iter()
+                    .map(|x| format!("\"{}\"", json_escape(x)))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        ));
+    }
+
+    pub fn add_timing(&mut self, phase: &str, duration: std::time::Duration) {
+        self.timings
+            .push((phase.to_string(), duration.as_secs_f64()));
+    }
+
+    fn entries_to_json(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|(k, v)| format!("  \"{}\": {}", json_escape(k), v))
+            .collect()
+    }
+
+    // the parameters and input characteristics recorded so far, without timings, on one line
+    pub fn config_to_json(&self) -> String {
+        let entries: Vec<String> = self
+            .entries_to_json()
+            .iter()
+            .map(|x| x.trim_start().to_string())
+            .collect();
+        format!("{{{}}}", entries.join(", "))
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut res = self.entries_to_json();
+        res.push(format!(
+            "  \"timings_s\": {{{}}}",
+            self.timings
+                .iter()
+                .map(|(k, v)| format!("\"{}\": {}", json_escape(k), v))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ));
+        res.push(format!(
+            "  \"peak_memory_kb\": {}",
+            peak_memory_kb()
+                .map(|x| x.to_string())
+                .unwrap_or_else(|| "null".to_string())
+        ));
+        format!("{{\n{}\n}}\n", res.join(",\n"))
+    }
+
+    // metrics in Prometheus text exposition format; counts not recorded in this run are omitted
+    pub fn to_prometheus(&self) -> String {
+        let mut res = String::new();
+        for (key, help) in METRICS.iter() {
+            if let Some((_, v)) = self.entries.iter().find(|(k, _)| k == key) {
+                res.push_str(&format!(
+                    "# HELP panacus_{} {}\n# TYPE panacus_{} gauge\npanacus_{} {}\n",
+                    key, help, key, key, v
+                ));
+            }
+        }
+        if !self.timings.is_empty() {
+            res.push_str(
+                "# HELP panacus_phase_duration_seconds Duration of each phase of the run\n",
+            );
+            res.push_str("# TYPE panacus_phase_duration_seconds gauge\n");
+            for (phase, t) in self.timings.iter() {
+                res.push_str(&format!(
+                    "panacus_phase_duration_seconds{{phase=\"{}\"}} {}\n",
+                    phase, t
+                ));
+            }
+        }
+        res
+    }
+}
+
 //
 // helper functions
 //
 
+pub fn log_throughput(phase: &str, bytes: u64, duration: std::time::Duration) {
+    let mb = bytes as f64 / 1e6;
+    let secs = duration.as_secs_f64();
+    log::info!(
+        "{} processed {:.1} MB in {:.2}s ({:.1} MB/s)",
+        phase,
+        mb,
+        secs,
+        if secs > 0.0 { mb / secs } else { f64::INFINITY }
+    );
+}
+
+// mean and unbiased sample variance of the given values; the variance is undefined for a single
+// value
+pub fn mean_variance(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = if values.len() > 1 {
+        values.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1.0)
+    } else {
+        f64::NAN
+    };
+    (mean, variance)
+}
+
+pub fn json_escape(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\t' => res.push_str("\\t"),
+            c if (c as u32) < 0x20 => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+pub fn normalize_id(s: &[u8]) -> Cow<'_, [u8]> {
+    // leading zeros of numeric IDs are removed, but a single zero is kept
+    if s.len() > 1 && s[0] == b'0' && s.iter().all(|x| x.is_ascii_digit()) {
+        let k = s.iter().position(|&x| x != b'0').unwrap_or(s.len() - 1);
+        Cow::Owned(s[k..].to_vec())
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
+pub fn percent_decode(s: &[u8]) -> Cow<'_, [u8]> {
+    // URL-style decoding of %XX escapes; malformed escapes are kept as they are
+    if !s.contains(&b'%') {
+        return Cow::Borrowed(s);
+    }
+    let hex = |i: usize| s.get(i).and_then(|&c| (c as char).to_digit(16));
+    let mut res = Vec::with_capacity(s.len());
+    let mut i = 0;
+    while i < s.len() {
+        match (s[i], hex(i + 1), hex(i + 2)) {
+            (b'%', Some(a), Some(b)) => {
+                res.push((a * 16 + b) as u8);
+                i += 3;
+            }
+            (c, _, _) => {
+                res.push(c);
+                i += 1;
+            }
+        }
+    }
+    Cow::Owned(res)
+}
+
+pub fn peak_memory_kb() -> Option<usize> {
+    // only available on Linux
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find(|l| l.starts_with("VmHWM:"))
+        .and_then(|l| l.split_whitespace().nth(1))
+        .and_then(|x| x.parse().ok())
+}
+
 pub fn intersects(v: &[(usize, usize)], el: &(usize, usize)) -> bool {
     // this code assumes that intervals of v are (i) sorted (ii) non-overlapping
 